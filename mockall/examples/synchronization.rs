@@ -1,12 +1,12 @@
 // vim: tw=80
-//! Add synchronization to multiple tests that are accessing the same mock
+//! Multiple tests that are accessing the same mock
 //!
-//! When mockall mocks a function or static method, it does so globally. This
-//! can cause hard to debug and non-deterministic failures when one test
-//! overwrites the mock that another test is depending on. The solution to this
-//! is to add some form of synchronization so that tests that depend on a
-//! specific mock will not run in parallel. This is easily achieved using a
-//! Mutex and the lazy_static crate.
+//! When mockall mocks a function or static method, it does so globally. That
+//! could cause hard to debug and non-deterministic failures when one test
+//! overwrites the mock that another test is depending on.  So every `Context`
+//! holds a lock shared by all of its mock's static methods.  Tests that depend
+//! on a specific mock will not run in parallel, without needing any
+//! synchronization of their own.
 #![deny(warnings)]
 
 use mockall_double::double;
@@ -21,6 +21,9 @@ pub mod my_mock {
         pub fn one() -> u32 {
             1
         }
+        pub fn two() -> u32 {
+            2
+        }
     }
 }
 
@@ -29,34 +32,15 @@ use my_mock::Thing;
 
 fn main() {
     println!("1 == {}", Thing::one());
+    println!("2 == {}", Thing::two());
 }
 
 #[cfg(test)]
 mod test {
     use crate::my_mock::MockThing;
-    use lazy_static::lazy_static;
-    use std::sync::{Mutex, MutexGuard};
-
-    lazy_static! {
-        static ref MTX: Mutex<()> = Mutex::new(());
-    }
-
-    // When a test panics, it will poison the Mutex. Since we don't actually
-    // care about the state of the data we ignore that it is poisoned and grab
-    // the lock regardless.  If you just do `let _m = &MTX.lock().unwrap()`, one
-    // test panicking will cause all other tests that try and acquire a lock on
-    // that Mutex to also panic.
-    fn get_lock(m: &'static Mutex<()>) -> MutexGuard<'static, ()> {
-        match m.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => poisoned.into_inner(),
-        }
-    }
 
     #[test]
     fn test_1() {
-        let _m = get_lock(&MTX);
-
         let ctx = MockThing::one_context();
         ctx.expect().returning(|| 1);
         let expected = 1;
@@ -65,11 +49,20 @@ mod test {
 
     #[test]
     fn test_2() {
-        let _m = get_lock(&MTX);
-
         let ctx = MockThing::one_context();
         ctx.expect().returning(|| 2);
         let expected = 2;
         assert_eq!(expected, MockThing::one())
     }
+
+    // The lock is shared by all of MockThing's static methods, and a single
+    // test may hold several Contexts.
+    #[test]
+    fn test_3() {
+        let one_ctx = MockThing::one_context();
+        let two_ctx = MockThing::two_context();
+        one_ctx.expect().returning(|| 3);
+        two_ctx.expect().returning(|| 4);
+        assert_eq!(7, MockThing::one() + MockThing::two())
+    }
 }
@@ -30,8 +30,13 @@
 //! * [`Static Return values`](#static-return-values)
 //! * [`Matching arguments`](#matching-arguments)
 //! * [`Call counts`](#call-counts)
+//! * [`Default actions`](#default-actions)
 //! * [`Sequences`](#sequences)
 //! * [`Checkpoints`](#checkpoints)
+//! * [`Collecting failures`](#collecting-failures)
+//! * [`Waiting for expectations`](#waiting-for-expectations)
+//! * [`Call history`](#call-history)
+//! * [`Passthrough`](#passthrough)
 //! * [`Reference arguments`](#reference-arguments)
 //! * [`Reference return values`](#reference-return-values)
 //! * [`impl Trait`](#impl-trait)
@@ -72,11 +77,11 @@
 //!
 //! ## Static Return values
 //!
-//! Every expectation must have an associated return value (though when the
-//! **nightly** feature is enabled expectations will automatically return the
-//! default values of their return types, if their return types implement
-//! `Default`.).  For methods that return a `static` value, the macros will
-//! generate an `Expectation` struct like
+//! Every expectation must have an associated return value (though
+//! expectations will automatically return the default values of their return
+//! types, if their return types implement `Default`.).  For methods that
+//! return a `static` value, the macros will generate an `Expectation` struct
+//! like
 //! [`this`](examples::__mock_MockFoo_Foo::__foo::Expectation).
 //! There are two ways to set such an expectation's return value: with a
 //! constant
@@ -272,6 +277,40 @@
 //!     .return_const(None);
 //! ```
 //!
+//! ### Matching order
+//!
+//! Expectations can also be searched newest first, with
+//! [`MatchOrder::NewestFirst`].  Then a later expectation overrides any earlier
+//! one that matches the same calls.  That's handy when a test fixture sets up
+//! broad expectations, and an individual test needs to replace one of them
+//! without a checkpoint.  `match_order` sets the order for every method of a
+//! mock object, and `match_order_foo` for just the `foo` method.  For static
+//! methods, use the method's `Context::match_order`.
+//!
+//! ```
+//! # use mockall::*;
+//! # use mockall::predicate::*;
+//! #[automock]
+//! trait Foo {
+//!     fn foo(&self, x: u32) -> u32;
+//! }
+//!
+//! fn fixture() -> MockFoo {
+//!     let mut mock = MockFoo::new();
+//!     mock.match_order(MatchOrder::NewestFirst);
+//!     mock.expect_foo()
+//!         .return_const(0u32);
+//!     mock
+//! }
+//!
+//! let mut mock = fixture();
+//! mock.expect_foo()
+//!     .with(eq(5))
+//!     .return_const(50u32);
+//! assert_eq!(50, mock.foo(5));
+//! assert_eq!(0, mock.foo(6));
+//! ```
+//!
 //! ## Call counts
 //!
 //! By default, every expectation is allowed to be called an unlimited number of
@@ -299,6 +338,60 @@
 //! [`never`](examples::__mock_MockFoo_Foo::__foo::Expectation::never) and
 //! [`times`](examples::__mock_MockFoo_Foo::__foo::Expectation::times).
 //!
+//! ## Default actions
+//!
+//! Sometimes a method's behavior is needed by many tests, but its calls aren't
+//! what those tests are about.  Rather than creating an expectation, which
+//! requires a call count and might shadow the expectations that each test
+//! adds, set a default action with `on_call_*`.  A default action accepts the
+//! same matchers and return values as an expectation, but it's only used when
+//! no expectation matches.  It may be called any number of times, including
+//! zero, and checkpoints leave it in place.  Static methods' contexts have an
+//! `on_call` method, too.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock]
+//! trait Foo {
+//!     fn foo(&self, x: u32) -> u32;
+//! }
+//!
+//! fn fixture() -> MockFoo {
+//!     let mut mock = MockFoo::new();
+//!     mock.on_call_foo()
+//!         .return_const(0u32);
+//!     mock
+//! }
+//!
+//! let mut mock = fixture();
+//! mock.expect_foo()
+//!     .with(predicate::eq(5))
+//!     .times(1)
+//!     .return_const(25u32);
+//! assert_eq!(mock.foo(5), 25);
+//! assert_eq!(mock.foo(6), 0);
+//! ```
+//!
+//! Mock objects created with `new_nice` go further.  When neither an
+//! expectation nor a default action matches a call, a nice mock returns the
+//! default value of the method's return type instead of failing.  Conversely,
+//! a mock object created with `new_strict` only allows calls that are handled
+//! by expectations with a call count.  It fails calls handled by default
+//! actions or by expectations that may be called any number of times.
+//!
+//! ```should_panic(expected = "has no call count, which strict mocks require")
+//! # use mockall::*;
+//! #[automock]
+//! trait Foo {
+//!     fn foo(&self, x: u32) -> u32;
+//! }
+//!
+//! let mut mock = MockFoo::new_strict();
+//! mock.expect_foo()
+//!     .return_const(0u32);
+//! mock.foo(5);    // Panics!  The expectation has no call count.
+//! ```
+//!
 //! ## Sequences
 //!
 //! By default expectations may be matched in any order.  But it's possible to
@@ -331,6 +424,134 @@
 //! # }
 //! ```
 //!
+//! Expectations in a sequence may also have a bounded range of call counts.
+//! Such an expectation may be called repeatedly until the sequence moves on,
+//! which happens when the next expectation in the sequence is called.  That's
+//! handy for protocols that retry a step until it succeeds.  But every
+//! expectation in a sequence needs an upper bound on its call count, so
+//! `times(1..)`, or no call count at all, isn't allowed.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock]
+//! trait Foo {
+//!     fn send(&self) -> bool;
+//!     fn commit(&self);
+//! }
+//!
+//! let mut seq = Sequence::new();
+//! let mut mock = MockFoo::new();
+//! mock.expect_send()
+//!     .times(1..5)
+//!     .in_sequence(&mut seq)
+//!     .return_const(true);
+//! mock.expect_commit()
+//!     .times(1)
+//!     .in_sequence(&mut seq)
+//!     .return_const(());
+//!
+//! mock.send();
+//! mock.send();
+//! mock.commit();
+//! ```
+//!
+//! [`Sequence::verify_complete`] checks that every expectation in a sequence
+//! was satisfied, returning the failures instead of panicking.  With
+//! [`Sequence::verify_on_drop`], the `Sequence` will also do that check when
+//! it's dropped.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock]
+//! trait Foo {
+//!     fn open(&self);
+//!     fn close(&self);
+//! }
+//!
+//! let mut seq = Sequence::new();
+//! let mut mock = MockFoo::new();
+//! mock.expect_open()
+//!     .times(1)
+//!     .in_sequence(&mut seq)
+//!     .return_const(());
+//! mock.expect_close()
+//!     .times(1)
+//!     .in_sequence(&mut seq)
+//!     .return_const(());
+//!
+//! mock.open();
+//! assert!(seq.verify_complete().is_err());
+//! mock.close();
+//! seq.verify_complete().unwrap();
+//! ```
+//!
+//! When the code under test makes its calls from several threads, they may
+//! arrive in the right order by design, but race to get started.  A sequence
+//! created with [`Sequence::rendezvous`] lets a call that arrives too early
+//! wait, up to a timeout, for the earlier members to be satisfied.  Mocked
+//! `async fn`s wait without blocking their executor.
+//!
+//! A `Sequence` imposes a total order.  When only some calls must be ordered,
+//! say that B and C may happen in any order but both after A, use `after`
+//! instead.  Every expectation has a `handle` method that returns an
+//! [`ExpectationHandle`], and an `after` method that only allows the
+//! expectation to be called once the handle's expectation has been satisfied.
+//!
+//! ```should_panic(expected = "Method order violation")
+//! # use mockall::*;
+//! #[automock]
+//! trait Foo {
+//!     fn a(&self);
+//!     fn b(&self);
+//! }
+//!
+//! let mut mock = MockFoo::new();
+//! let a = mock.expect_a()
+//!     .times(1)
+//!     .return_const(())
+//!     .handle();
+//! mock.expect_b()
+//!     .times(1)
+//!     .after(&a)
+//!     .return_const(());
+//! mock.b();   // Panics!  a hasn't been called yet.
+//! ```
+//!
+//! Some protocols need more than an order.  The same method may behave
+//! differently in each phase, like a `query` that fails before `auth` and
+//! succeeds afterwards.  A [`StateMachine`] models that.  An expectation
+//! with `when_state` only matches while the machine is in a given state, and
+//! one with `then_state` moves the machine to a new state when called.
+//!
+//! ```
+//! # use mockall::*;
+//! #[derive(Clone, Debug, PartialEq)]
+//! enum State { Connected, Authenticated }
+//!
+//! #[automock]
+//! trait Conn {
+//!     fn auth(&self);
+//!     fn query(&self) -> Option<u32>;
+//! }
+//!
+//! let machine = StateMachine::new(State::Connected);
+//! let mut mock = MockConn::new();
+//! mock.expect_query()
+//!     .when_state(&machine, State::Connected)
+//!     .return_const(None);
+//! mock.expect_auth()
+//!     .when_state(&machine, State::Connected)
+//!     .then_state(&machine, State::Authenticated)
+//!     .return_const(());
+//! mock.expect_query()
+//!     .when_state(&machine, State::Authenticated)
+//!     .return_const(Some(42));
+//!
+//! assert_eq!(mock.query(), None);
+//! mock.auth();
+//! assert_eq!(mock.query(), Some(42));
+//! ```
+//!
 //! ## Checkpoints
 //!
 //! Sometimes its useful to validate all expectations mid-test, throw them away,
@@ -375,6 +596,186 @@
 //! # }
 //! ```
 //!
+//! ## Collecting failures
+//!
+//! Normally a mock panics at the first sign of trouble, so a single test run
+//! only reveals the first problem.  Worse, if the mock is called from a worker
+//! thread, the panic may never reach the test at all.  A [`Verifier`] changes
+//! that.  While it's alive, expectations created on the same thread record
+//! their failures instead of panicking.  [`Verifier::verify`] then returns all
+//! of them at once.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock]
+//! trait Foo {
+//!     fn foo(&self) -> u32;
+//! }
+//!
+//! let verifier = Verifier::new();
+//! let mut mock = MockFoo::new();
+//! mock.expect_foo()
+//!     .times(1)
+//!     .return_const(42u32);
+//! std::thread::spawn(move || {
+//!     mock.foo();
+//!     mock.foo();     // Called too many times, but doesn't panic
+//! }).join().unwrap();
+//!
+//! let errors = verifier.verify().unwrap_err();
+//! assert_eq!(errors.len(), 1);
+//! ```
+//!
+//! A call that matches no expectation still panics, because the mock has
+//! nothing to return.  But it's recorded first, so it won't be lost.
+//!
+//! To route failures somewhere else entirely, like a custom test reporter or a
+//! channel, install a hook with [`set_failure_handler`].  Like a `Verifier`,
+//! it only applies to expectations created on the same thread.
+//!
+//! ## Waiting for expectations
+//!
+//! When the code under test calls a mock from a background thread or task, the
+//! test may finish before the call happens.  Rather than sleeping, the test
+//! can wait for the mock's expectations to be satisfied.  `wait_satisfied`
+//! blocks until every expectation has been called at least as many times as it
+//! requires, or until the timeout expires.  On timeout it returns the
+//! expectations that are still unsatisfied.  Async tests can `await` the
+//! mock's `satisfied` method instead.  Expectations of static methods aren't
+//! considered, since they don't belong to any mock object.
+//!
+//! ```
+//! # use mockall::*;
+//! # use std::{sync::Arc, thread, time::Duration};
+//! #[automock]
+//! trait Foo {
+//!     fn foo(&self, x: u32);
+//! }
+//!
+//! let mut mock = MockFoo::new();
+//! mock.expect_foo()
+//!     .times(1)
+//!     .return_const(());
+//! let mock = Arc::new(mock);
+//! let mock2 = mock.clone();
+//! thread::spawn(move || mock2.foo(42));
+//!
+//! mock.wait_satisfied(Duration::from_secs(5)).unwrap();
+//! # futures::executor::block_on(mock.satisfied());
+//! ```
+//!
+//! ## Call history
+//!
+//! Instead of setting every detail of an expectation up front, it's sometimes
+//! more convenient to inspect a mock's calls after the fact.  Once turned on
+//! with [`set_call_history_limit`], every mocked method records the arguments
+//! of each call it receives, whether or not any expectation matched it, up to
+//! the limit.  The history is available from the mock's `calls_foo` method, as
+//! a `Vec` of tuples with one [`Arg`] per argument.  Reference arguments are
+//! recorded as their owned types, like `String` for `&str`.  An `Arg` can be
+//! compared directly with a value of its type.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock]
+//! trait Foo {
+//!     fn foo(&self, x: u32, name: &str);
+//! }
+//!
+//! set_call_history_limit(1000);
+//! let mut mock = MockFoo::new();
+//! mock.expect_foo()
+//!     .return_const(());
+//! mock.foo(4, "four");
+//! mock.foo(5, "five");
+//!
+//! let calls = mock.calls_foo();
+//! assert_eq!(calls.len(), 2);
+//! assert_eq!(calls[0].0, 4);
+//! assert_eq!(calls[1].1, String::from("five"));
+//! ```
+//!
+//! Only arguments whose owned types are `Clone`, `Send`, and `'static` can be
+//! recorded.  For any other argument, including trait objects and generic
+//! parameters, [`Arg::value`] will return `None`, though the `Debug`
+//! representation of the `Arg` is still available when the argument
+//! implements `Debug`.
+//!
+//! Unlike expectations, the call history is not cleared by a checkpoint.  For
+//! static methods, the history is available from the method's `Context`
+//! object.  Like the expectations, it's cleared when the `Context` drops.
+//!
+//! The call history is off by default, so mocks that don't use it don't pay
+//! for copying their arguments.  The limit is shared by every test in the
+//! process, so tests that use the history should each set it.
+//!
+//! ### Argument captors
+//!
+//! A [`Captor`] is more selective.  It's attached to a single expectation, and
+//! stores a value computed from the arguments of each call that the
+//! expectation handles.  The computation can convert reference arguments into
+//! owned values, or extract what's needed from arguments that can't be
+//! recorded, like trait objects.  For anything else, `capturing` runs an
+//! arbitrary closure on the arguments.
+//!
+//! ```
+//! # use mockall::*;
+//! # use std::path::PathBuf;
+//! #[automock]
+//! trait Foo {
+//!     #[concretize]
+//!     fn open<P: AsRef<std::path::Path>>(&self, path: P, mode: &str);
+//! }
+//!
+//! let paths = Captor::new();
+//! let mut mock = MockFoo::new();
+//! mock.expect_open()
+//!     .withf(|_path, mode| mode == "r")
+//!     .capture(&paths, |path, _mode| path.as_ref().to_owned())
+//!     .return_const(());
+//! mock.open("/etc/passwd", "r");
+//!
+//! assert_eq!(paths.values(), [PathBuf::from("/etc/passwd")]);
+//! ```
+//!
+//! ## Passthrough
+//!
+//! Sometimes only a few methods of a large trait need to be mocked, and the
+//! rest should keep their real behavior.  For that, a trait's mock object can
+//! be built around a real implementation of the trait with `wrapping`.  Any
+//! call that matches no expectation will be forwarded to the real object,
+//! instead of panicking.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock]
+//! trait Storage {
+//!     fn get(&self, key: u32) -> Option<u32>;
+//!     fn len(&self) -> usize;
+//! }
+//!
+//! struct Real;
+//! impl Storage for Real {
+//!     fn get(&self, key: u32) -> Option<u32> { Some(key * 2) }
+//!     fn len(&self) -> usize { 100 }
+//! }
+//!
+//! let mut mock = MockStorage::wrapping(Real);
+//! mock.expect_get()
+//!     .with(predicate::eq(5))
+//!     .return_const(None);
+//! assert_eq!(None, mock.get(5));      // Handled by the expectation
+//! assert_eq!(Some(6), mock.get(3));   // Forwarded to Real
+//! assert_eq!(100, mock.len());        // Forwarded to Real
+//! ```
+//!
+//! The real object must be `Send + Sync + 'static`, and it must use the same
+//! associated types as the mock.  When a mock struct implements several
+//! traits, the real object must implement all of them.  Only methods that take
+//! `&self` or `&mut self` can be forwarded, and not generic methods, `async`
+//! methods, or methods whose signatures mention `Self`.  Those will still
+//! panic when no expectation matches.
+//!
 //! ## Reference arguments
 //!
 //! Mockall can mock methods with reference arguments, too.  There's one catch:
@@ -623,6 +1024,10 @@
 //! # }
 //! ```
 //!
+//! Or let
+//! [`returning_async`](#async-return-values)
+//! do the pinning for you.
+//!
 //! ## Mocking structs
 //!
 //! Mockall mocks structs as well as traits.  The problem here is a namespace
@@ -670,6 +1075,33 @@
 //! For structs with more than one `impl` block or that have unsupported
 //! `#[derive(X)]` attributes, e.g. `Clone`, see [`mock!`] instead.
 //!
+//! ### Partial mocks
+//!
+//! Within an `impl` block decorated with `#[automock]`, individual methods can
+//! be marked with [`#[mockall::real]`](attr.real.html).  The mock struct will
+//! keep those methods' original bodies, and mock only the rest.  That's useful
+//! for helper methods that merely combine the struct's other methods.
+//!
+//! ```
+//! # use mockall::automock;
+//! pub struct Thing{}
+//! #[automock]
+//! impl Thing {
+//!     pub fn read(&self) -> u32 {
+//!         // Do some I/O
+//!         # unimplemented!()
+//!     }
+//!     #[mockall::real]
+//!     pub fn read_twice(&self) -> u32 {
+//!         self.read() + self.read()
+//!     }
+//! }
+//!
+//! let mut mock = MockThing::default();
+//! mock.expect_read().return_const(21u32);
+//! assert_eq!(42, mock.read_twice());
+//! ```
+//!
 //! ## Generic methods
 //!
 //! Mocking generic methods is possible, but the exact process depends on
@@ -844,12 +1276,8 @@
 //!
 //! ## Static methods
 //!
-//! Mockall can also mock static methods.  But be careful!  The expectations are
-//! global.  If you want to use a static method in multiple tests, you must
-//! provide your own synchronization. See the [`synchronization
-//! example`](https://github.com/asomers/mockall/blob/master/mockall/examples/synchronization.rs)
-//! for a basic implementation. For ordinary methods, expectations are
-//! set on the mock object.  But static methods don't have any mock object.
+//! Mockall can also mock static methods.  For ordinary methods, expectations
+//! are set on the mock object.  But static methods don't have any mock object.
 //! Instead, you must create a `Context` object just to set their expectations.
 //!
 //! ```
@@ -864,6 +1292,40 @@
 //! assert_eq!(99, MockA::foo());
 //! ```
 //!
+//! Static expectations are global, so tests that use the same static method
+//! mustn't run at the same time.  Mockall takes care of that: every `Context`
+//! holds a lock shared by all of its mock's static methods, or by all of a
+//! mocked module's functions.  Creating a `Context` on one thread waits until
+//! other threads have dropped all of theirs for the same mock.  One thread may
+//! hold several `Context`s at once.  But like any other locks, a test that
+//! holds the `Context`s of several mocks should create them in the same order
+//! as every other test, or the tests may deadlock.  See the [`synchronization
+//! example`](https://github.com/asomers/mockall/blob/master/mockall/examples/synchronization.rs).
+//!
+//! Alternatively, `#[automock(statics = thread_local)]` keeps each thread's
+//! expectations separate.  Tests that use the same static method can then run
+//! in parallel without waiting for each other.  But calls only match
+//! expectations set on the same thread, so the code under test must run on the
+//! test's thread.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock(statics = thread_local)]
+//! pub trait A {
+//!     fn foo() -> u32;
+//! }
+//!
+//! let ctx = MockA::foo_context();
+//! ctx.expect().returning(|| 99);
+//! assert_eq!(99, MockA::foo());
+//! std::thread::spawn(|| {
+//!     // This thread has its own expectations
+//!     let ctx = MockA::foo_context();
+//!     ctx.expect().returning(|| 42);
+//!     assert_eq!(42, MockA::foo());
+//! }).join().unwrap();
+//! ```
+//!
 //! A common pattern is mocking a trait with a constructor method.  In this case,
 //! you can easily set the mock constructor method to return a mock object.
 //!
@@ -961,6 +1423,36 @@
 //! # }
 //! ```
 //!
+//! ### Static contexts
+//!
+//! A mock with several static methods would need one context object for each.
+//! Instead, its `static_context` method returns a single context object that
+//! covers all of them.  It has `expect_foo` and `on_call_foo` methods for every
+//! static method, a `checkpoint` method that checkpoints every static method,
+//! and it verifies all of them when it drops.  For mocked modules, the
+//! equivalent is the module's `context` function.  Neither is generated if it
+//! would collide with the original: a struct's own `static_context` method, or
+//! a module's own `context` or `StaticContext` item.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock]
+//! pub trait A {
+//!     fn build(x: u32) -> Self;
+//!     fn helper() -> i32;
+//! }
+//!
+//! # fn main() {
+//! let ctx = MockA::static_context();
+//! ctx.expect_build()
+//!     .returning(|_| MockA::default());
+//! ctx.expect_helper()
+//!     .return_const(42);
+//! let _mock = MockA::build(5);
+//! assert_eq!(42, MockA::helper());
+//! # }
+//! ```
+//!
 //! One more thing: Mockall normally creates a zero-argument `new` method for
 //! every mock struct.  But it *won't* do that when mocking a struct that
 //! already has a method named `new`.  The `default` method will still be
@@ -1064,6 +1556,28 @@
 //! # }
 //! ```
 //!
+//! Failure messages identify an expectation by its method and matcher, like
+//! `MockFoo::foo: Expectation(var == 4)`.  When that isn't enough to tell
+//! expectations or mock objects apart, give them names.  Expectations have a
+//! `named` method, and every mock struct with non-static methods has a
+//! `named` constructor.
+//!
+//! ```should_panic(expected = "MockFoo[replica]::foo: Expectation")
+//! # use mockall::*;
+//! #[automock]
+//! trait Foo {
+//!     fn foo(&self);
+//! }
+//!
+//! let _primary = MockFoo::named("primary");
+//! let mut replica = MockFoo::named("replica");
+//! replica.expect_foo()
+//!     .named("write")
+//!     .times(1)
+//!     .return_const(());
+//! // Panics!
+//! ```
+//!
 //! ## Async Traits
 //!
 //! Async traits aren't yet (as of 1.47.0) a part of the Rust language.  But
@@ -1096,6 +1610,64 @@
 //! # fn main() {}
 //! ```
 //!
+//! ### Async return values
+//!
+//! Expectations for `async fn` methods, including `async_trait` ones, can
+//! return values synchronously with the usual methods, like `return_const`.
+//! But when the return value depends on something else that happens
+//! asynchronously, use `returning_async`.  Its closure returns a future, which
+//! the mock method will `.await`.  It also works for methods returning
+//! `impl Future`, sparing you the `Box::pin`.
+//!
+//! ```
+//! # use async_trait::async_trait;
+//! # use futures::{channel::mpsc, executor::block_on, lock::Mutex, StreamExt};
+//! # use mockall::*;
+//! # use std::sync::Arc;
+//! #[automock]
+//! #[async_trait]
+//! trait Foo {
+//!     async fn foo(&self) -> u32;
+//! }
+//!
+//! let (mut tx, rx) = mpsc::channel(1);
+//! let rx = Arc::new(Mutex::new(rx));
+//! let mut mock = MockFoo::new();
+//! mock.expect_foo()
+//!     .returning_async(move || {
+//!         let rx = rx.clone();
+//!         async move { rx.lock().await.next().await.unwrap() }
+//!     });
+//! tx.try_send(42).unwrap();
+//! assert_eq!(42, block_on(mock.foo()));
+//! ```
+//!
+//! To check how the code under test behaves while a call is still in flight,
+//! use `returning_handle`.  The mock method's future will stay pending until
+//! the test completes it through the returned [`FutureHandle`].  The handle
+//! can also tell whether the future was ever polled, or was dropped before it
+//! finished.
+//!
+//! ```
+//! # use async_trait::async_trait;
+//! # use futures::FutureExt;
+//! # use mockall::*;
+//! #[automock]
+//! #[async_trait]
+//! trait Foo {
+//!     async fn fetch(&self) -> Result<u32, String>;
+//! }
+//!
+//! let mut mock = MockFoo::new();
+//! let handle = mock.expect_fetch()
+//!     .returning_handle();
+//! let mut fut = mock.fetch();
+//! assert!((&mut fut).now_or_never().is_none());
+//! assert!(handle.is_polled());
+//! handle.fail(String::from("timed out"));
+//! assert_eq!(Some(Err(String::from("timed out"))), fut.now_or_never());
+//! ```
+//!
 //! ## Crate features
 //!
 //! Mockall has a **nightly** feature.  Currently this feature has two
@@ -1103,22 +1675,24 @@
 //!
 //! * The compiler will produce better error messages.
 //!
-//! * Expectations for methods whose return type implements `Default` needn't
-//!   have their return values explicitly set.  Instead, they will automatically
-//!   return the default value.
+//! * Generic methods whose return type implements `Default` can return the
+//!   default value, and generic arguments that implement `Debug` are printed
+//!   in failure messages.  Without **nightly**, that only works for concrete
+//!   types.
 //!
-//! With **nightly** enabled, you can omit the return value like this:
+//! With **nightly** enabled, you can omit the return value even when it's
+//! generic, like this:
 #![cfg_attr(feature = "nightly", doc = "```")]
 #![cfg_attr(not(feature = "nightly"), doc = "```should_panic")]
 //! # use mockall::*;
 //! #[automock]
 //! trait Foo {
-//!     fn foo(&self) -> Vec<u32>;
+//!     fn foo<T: 'static>(&self) -> T;
 //! }
 //!
 //! let mut mock = MockFoo::new();
-//! mock.expect_foo();
-//! assert!(mock.foo().is_empty());
+//! mock.expect_foo::<u32>();
+//! assert_eq!(mock.foo::<u32>(), 0);
 //! ```
 //!
 //! ## Examples
@@ -1146,14 +1720,30 @@
 use downcast::*;
 use std::{
     any,
-    fmt::{self, Debug, Formatter},
+    cell::RefCell,
+    collections::{HashMap, VecDeque, hash_map::Entry},
+    error,
+    fmt::{self, Debug, Display, Formatter},
+    future::Future,
     marker::PhantomData,
+    mem,
     ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo,
           RangeToInclusive},
+    panic::Location,
+    pin::Pin,
+    slice,
     sync::{
         Arc,
-        atomic::{AtomicUsize, Ordering}
+        Condvar,
+        Mutex,
+        PoisonError,
+        Weak,
+        atomic::{self, AtomicBool, AtomicUsize, Ordering}
     },
+    task::{Context, Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+    vec,
 };
 
 #[doc(hidden)]
@@ -1256,6 +1846,20 @@ pub mod examples;
 /// }
 /// ```
 ///
+/// The `statics = thread_local` metaitem keeps the expectations of static
+/// methods, or of a module's functions, in thread-local storage.  Tests on
+/// different threads can then use them at the same time, as long as the code
+/// under test calls them from the test's own thread.  See
+/// [`Static methods`](../mockall/index.html#static-methods).
+///
+/// ```
+/// # use mockall_derive::*;
+/// #[automock(statics = thread_local)]
+/// trait Foo {
+///     fn foo() -> u32;
+/// }
+/// ```
+///
 /// [`checkpoint`]: ../mockall/index.html#checkpoints
 ///
 /// # Limitations
@@ -1324,6 +1928,19 @@ pub use mockall_derive::automock;
 /// ```
 pub use mockall_derive::concretize;
 
+/// Decorates a method in an inherent `impl` block to tell
+/// [`#[automock]`](attr.automock.html) not to mock it.
+///
+/// The mock struct will have a copy of the method with its original body,
+/// instead of a mock method and an `expect_` method.  Since the mock struct
+/// has none of the original struct's fields, this is mostly useful for methods
+/// that only call other methods.  See
+/// [`Partial mocks`](index.html#partial-mocks) for an example.
+///
+/// NB: This attribute must be imported with its canonical name.  It won't work
+/// otherwise!
+pub use mockall_derive::real;
+
 /// Manually mock a structure.
 ///
 /// Sometimes `automock` can't be used.  In those cases you can use `mock!`,
@@ -1414,20 +2031,82 @@ pub use mockall_derive::concretize;
 /// ```
 pub use mockall_derive::mock;
 
+/// The order in which a method's expectations are searched for one that
+/// matches a call.
+///
+/// Set it for every method of a mock object with `MockFoo::match_order`, for
+/// a single method with `MockFoo::match_order_foo`, or for a static method with
+/// its `Context::match_order`.  See
+/// [`Matching order`](index.html#matching-order).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MatchOrder {
+    /// The first expectation that was added and matches the call handles it.
+    /// This is the default.
+    OldestFirst,
+    /// The last expectation that was added and matches the call handles it,
+    /// so newer expectations override older ones.
+    NewestFirst
+}
+
+impl Default for MatchOrder {
+    fn default() -> Self {
+        MatchOrder::OldestFirst
+    }
+}
+
 #[doc(hidden)]
-pub trait AnyExpectations : Any + Send + Sync {}
+pub trait AnyExpectations : Any + Send + Sync {
+    /// Remove every expectation except for default actions, so the caller
+    /// can verify them by dropping them.
+    fn checkpoint(&mut self) -> Vec<Box<dyn Send>>;
+
+    /// Set the order in which expectations are matched.
+    fn match_order(&mut self, order: MatchOrder);
+
+    /// Describe every expectation that hasn't yet been called as many times as
+    /// it requires.
+    fn unsatisfied(&self) -> Vec<String>;
+}
 downcast!(dyn AnyExpectations);
 
+/// The return value of an expectation for an `async fn`: either a value, or
+/// a future set by `returning_async` that the mock method must `.await`.
+#[doc(hidden)]
+pub enum AsyncOutput<O> {
+    Ready(O),
+    Pending(Pin<Box<dyn Future<Output=O> + Send>>)
+}
+
+impl<O: Default> Default for AsyncOutput<O> {
+    fn default() -> Self {
+        AsyncOutput::Ready(O::default())
+    }
+}
+
+impl<O> AsyncOutput<O> {
+    pub async fn resolve(self) -> O {
+        match self {
+            AsyncOutput::Ready(o) => o,
+            AsyncOutput::Pending(f) => f.await
+        }
+    }
+}
+
 #[doc(hidden)]
 pub trait ReturnDefault<O> {
     fn maybe_return_default() -> Option<O>;
     fn return_default() -> Result<O, &'static str>;
 }
 
-#[derive(Default)]
 #[doc(hidden)]
 pub struct DefaultReturner<O>(PhantomData<O>);
 
+impl<O> Default for DefaultReturner<O> {
+    fn default() -> Self {
+        DefaultReturner(PhantomData)
+    }
+}
+
 ::cfg_if::cfg_if! {
     if #[cfg(feature = "nightly")] {
         impl<O> ReturnDefault<O> for DefaultReturner<O> {
@@ -1456,12 +2135,57 @@ pub struct DefaultReturner<O>(PhantomData<O>);
             }
 
             fn return_default() -> Result<O, &'static str> {
-                Err("Returning default values requires the \"nightly\" feature")
+                Err(concat!("Can only return default values for types that ",
+                    "impl std::Default.  For generic types, that requires the ",
+                    "\"nightly\" feature"))
             }
         }
     }
 }
 
+// Default return values work on stable Rust through autoref-based
+// specialization.  Generated code calls `try_default` on a
+// `&&DefaultReturner<O>`.  If `O: Default`, method resolution picks
+// `ViaDefault`, which matches the receiver exactly.  Otherwise it auto-derefs
+// once and falls back to `ViaReturnDefault`, which on nightly may still
+// specialize.  This only works where `O` is a concrete type; in generic code
+// the fallback is always chosen.
+
+/// Return a default value for a type known to implement `Default`
+#[doc(hidden)]
+pub trait ViaDefault<O> {
+    fn maybe_default(&self) -> Option<O>;
+    fn try_default(&self) -> Result<O, &'static str>;
+}
+
+impl<O: Default> ViaDefault<O> for &DefaultReturner<O> {
+    fn maybe_default(&self) -> Option<O> {
+        Some(O::default())
+    }
+
+    fn try_default(&self) -> Result<O, &'static str> {
+        Ok(O::default())
+    }
+}
+
+/// Return a default value if the type's `Default` impl can be found by
+/// specialization.
+#[doc(hidden)]
+pub trait ViaReturnDefault<O> {
+    fn maybe_default(&self) -> Option<O>;
+    fn try_default(&self) -> Result<O, &'static str>;
+}
+
+impl<O> ViaReturnDefault<O> for DefaultReturner<O> {
+    fn maybe_default(&self) -> Option<O> {
+        <Self as ReturnDefault<O>>::maybe_return_default()
+    }
+
+    fn try_default(&self) -> Result<O, &'static str> {
+        <Self as ReturnDefault<O>>::return_default()
+    }
+}
+
 #[doc(hidden)]
 pub struct MaybeDebugger<'a, T>(pub &'a T);
 ::cfg_if::cfg_if! {
@@ -1487,6 +2211,231 @@ pub struct MaybeDebugger<'a, T>(pub &'a T);
     }
 }
 
+// Arguments are printed the same way as default values are returned:
+// generated code calls `debug_arg` on a `&&MaybeDebugger<T>`, which resolves
+// to `ViaDebug` if `T: Debug` and to `ViaMaybeDebug` otherwise.
+
+/// Print an argument whose type is known to implement `Debug`
+#[doc(hidden)]
+pub trait ViaDebug<'a, T> {
+    fn debug_arg(&self) -> &'a T;
+}
+
+impl<'a, T: Debug> ViaDebug<'a, T> for &MaybeDebugger<'a, T> {
+    fn debug_arg(&self) -> &'a T {
+        self.0
+    }
+}
+
+/// Print an argument as `?`, unless its `Debug` impl can be found by
+/// specialization.
+#[doc(hidden)]
+pub trait ViaMaybeDebug<'a, T> {
+    fn debug_arg(&self) -> MaybeDebugger<'a, T>;
+}
+
+impl<'a, T> ViaMaybeDebug<'a, T> for MaybeDebugger<'a, T> {
+    fn debug_arg(&self) -> MaybeDebugger<'a, T> {
+        MaybeDebugger(self.0)
+    }
+}
+
+/// Describe how a predicate rejected an argument, for the "No matching
+/// expectation found" panic message.
+#[doc(hidden)]
+pub fn explain_rejection(argname: &str, tree: &dyn Display) -> String {
+    let tree = tree.to_string();
+    let mut lines = tree.lines();
+    let mut s = format!("\n    {}: {}", argname, lines.next().unwrap_or(""));
+    let indent = " ".repeat(argname.len() + 6);
+    for line in lines {
+        s.push('\n');
+        s.push_str(&indent);
+        s.push_str(line);
+    }
+    s
+}
+
+/// Describe where an expectation was created, and where the mock was called.
+#[doc(hidden)]
+pub fn whence(set: Option<&Location<'_>>, called: Option<&Location<'_>>)
+    -> String
+{
+    match (set, called) {
+        (Some(s), Some(c)) => format!(" (set at {}, called at {})", s, c),
+        (Some(s), None) => format!(" (set at {})", s),
+        (None, Some(c)) => format!(" (called at {})", c),
+        (None, None) => String::new()
+    }
+}
+
+static CALL_HISTORY_LIMIT: AtomicUsize = AtomicUsize::new(0);
+
+/// Set how many calls each mock method records in its call history.
+///
+/// The limit starts at 0, which turns the call history off.  Once a method has
+/// recorded `limit` calls, its later calls aren't recorded, and their
+/// arguments are neither copied nor formatted.  The limit applies to all
+/// threads, and to calls made after it is set.  See
+/// [`Call history`](index.html#call-history).
+///
+/// # Examples
+/// ```
+/// # use mockall::*;
+/// #[automock]
+/// trait Foo {
+///     fn foo(&self, x: u32);
+/// }
+///
+/// set_call_history_limit(2);
+/// let mut mock = MockFoo::new();
+/// mock.expect_foo()
+///     .return_const(());
+/// for i in 0..10 {
+///     mock.foo(i);
+/// }
+/// assert_eq!(mock.calls_foo().len(), 2);
+/// ```
+pub fn set_call_history_limit(limit: usize) {
+    CALL_HISTORY_LIMIT.store(limit, Ordering::Relaxed);
+}
+
+/// The limit set by [`set_call_history_limit`].
+#[doc(hidden)]
+pub fn call_history_limit() -> usize {
+    CALL_HISTORY_LIMIT.load(Ordering::Relaxed)
+}
+
+/// A single argument from a mock method's call history.
+///
+/// If the argument's type implements `ToOwned`, as all `Clone` types do, then
+/// `Arg` holds a copy of the argument's value, and prints it with the owned
+/// type's `Debug` implementation, if any.  Otherwise it holds only the
+/// argument's `Debug` representation.  Arguments whose types cannot be stored
+/// in the mock object, such as trait objects, types with non-`'static`
+/// lifetimes, or generic types, are recorded as `Arg<()>`.  See
+/// [`Call history`](index.html#call-history).
+pub struct Arg<T: ?Sized> {
+    value: Option<Arc<dyn any::Any + Send + Sync>>,
+    debug: ArgDebug,
+    _t: PhantomData<fn(&T)>
+}
+
+/// How to print an [`Arg`]
+#[derive(Clone)]
+enum ArgDebug {
+    /// The argument's `Debug` representation, formatted when it was recorded
+    Formatted(String),
+    /// Print the recorded value with this function
+    Value(fn(&(dyn any::Any + Send + Sync), &mut Formatter<'_>)
+        -> Result<(), fmt::Error>)
+}
+
+impl<T: ?Sized> Arg<T> {
+    /// Not for public consumption, but it must be public so the generated code
+    /// can call it.
+    #[doc(hidden)]
+    pub fn unrecorded(debug: String) -> Self {
+        Arg { value: None, debug: ArgDebug::Formatted(debug), _t: PhantomData }
+    }
+}
+
+impl<T: 'static> Arg<T> {
+    /// Return a reference to the argument's value, if it could be copied.
+    pub fn value(&self) -> Option<&T> {
+        self.value.as_ref().and_then(|v| v.downcast_ref::<T>())
+    }
+}
+
+impl<T: ?Sized> Clone for Arg<T> {
+    fn clone(&self) -> Self {
+        Arg {
+            value: self.value.clone(),
+            debug: self.debug.clone(),
+            _t: PhantomData
+        }
+    }
+}
+
+impl<T: ?Sized> Debug for Arg<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match (&self.debug, &self.value) {
+            (ArgDebug::Formatted(s), _) => f.write_str(s),
+            (ArgDebug::Value(g), Some(v)) => g(&**v, f),
+            (ArgDebug::Value(_), None) => f.write_str("?")
+        }
+    }
+}
+
+impl<T: PartialEq + 'static> PartialEq<T> for Arg<T> {
+    fn eq(&self, other: &T) -> bool {
+        self.value() == Some(other)
+    }
+}
+
+/// Records an argument as the owned type `O`, if possible.
+///
+/// The choice between [`RecordOwnedDebug`], [`RecordOwned`] and
+/// [`RecordDebug`] is made with "autoref specialization", so it works on
+/// stable Rust, but only for concrete types.  Only `RecordDebug` calls its
+/// `debug` closure; the others print the owned value when the `Arg` is
+/// printed.
+#[doc(hidden)]
+pub struct ArgRecorder<'a, T: ?Sized, O: ?Sized>(&'a T, PhantomData<fn(&O)>);
+
+impl<'a, T: ?Sized, O: ?Sized> ArgRecorder<'a, T, O> {
+    pub fn new(t: &'a T) -> Self {
+        ArgRecorder(t, PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait RecordOwnedDebug<O> {
+    fn record<F: FnOnce() -> String>(&self, debug: F) -> Arg<O>;
+}
+
+impl<'a, T, O> RecordOwnedDebug<O> for &ArgRecorder<'a, T, O>
+    where T: ?Sized + ToOwned<Owned = O>,
+          O: Debug + Send + Sync + 'static
+{
+    fn record<F: FnOnce() -> String>(&self, _debug: F) -> Arg<O> {
+        Arg {
+            value: Some(Arc::new(self.0.to_owned())),
+            debug: ArgDebug::Value(|v, f| v.downcast_ref::<O>().unwrap().fmt(f)),
+            _t: PhantomData
+        }
+    }
+}
+
+#[doc(hidden)]
+pub trait RecordOwned<O> {
+    fn record<F: FnOnce() -> String>(&self, debug: F) -> Arg<O>;
+}
+
+impl<'a, T, O> RecordOwned<O> for &&ArgRecorder<'a, T, O>
+    where T: ?Sized + ToOwned<Owned = O>,
+          O: Send + Sync + 'static
+{
+    fn record<F: FnOnce() -> String>(&self, _debug: F) -> Arg<O> {
+        Arg {
+            value: Some(Arc::new(self.0.to_owned())),
+            debug: ArgDebug::Value(|_, f| f.write_str("?")),
+            _t: PhantomData
+        }
+    }
+}
+
+#[doc(hidden)]
+pub trait RecordDebug<O: ?Sized> {
+    fn record<F: FnOnce() -> String>(&self, debug: F) -> Arg<O>;
+}
+
+impl<'a, T: ?Sized, O: ?Sized> RecordDebug<O> for ArgRecorder<'a, T, O> {
+    fn record<F: FnOnce() -> String>(&self, debug: F) -> Arg<O> {
+        Arg::unrecorded(debug())
+    }
+}
+
 // Though it's not entirely correct, we treat usize::max_value() as
 // approximately infinity.
 #[derive(Debug)]
@@ -1586,10 +2535,14 @@ impl Times {
         self.count.load(Ordering::Relaxed) >= self.range.0.end - 1
     }
 
-    /// Is it required that this expectation be called an exact number of times,
-    /// or may it be satisfied by a range of call counts?
-    pub fn is_exact(&self) -> bool {
-        (self.range.0.end - self.range.0.start) == 1
+    /// Is there a limit on how many times this expectation may be called?
+    pub fn is_bounded(&self) -> bool {
+        self.range.0.end != TimesRange::default().0.end
+    }
+
+    /// May this expectation be called any number of times, including zero?
+    pub fn is_unbounded(&self) -> bool {
+        self.range.0 == TimesRange::default().0
     }
 
     /// Has this expectation already been called the minimum required number of
@@ -1642,40 +2595,329 @@ pub struct SeqHandle {
 }
 
 impl SeqHandle {
-    /// Tell the Sequence that this expectation has been fully satisfied
-    pub fn satisfy(&self) {
-        self.inner.satisfy(self.seq);
+    /// Has the [`Sequence`] already reported this expectation as unsatisfied?
+    pub fn is_reported(&self) -> bool {
+        self.inner.state.lock().unwrap().steps[self.seq].reported
+    }
+
+    /// Tell the Sequence whether this expectation has been called at least as
+    /// many times as it requires, and how to describe it.
+    pub fn update(&self, satisfied: bool, desc: String,
+        location: Option<&'static Location<'static>>)
+    {
+        let wakers = {
+            let mut state = self.inner.state.lock().unwrap();
+            let step = &mut state.steps[self.seq];
+            step.satisfied = satisfied;
+            step.desc = desc;
+            step.location = location;
+            self.inner.changed.notify_all();
+            mem::take(&mut state.wakers)
+        };
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Return a future that resolves once this handle may be called without
+    /// waiting, or once a rendezvous Sequence's timeout expires.
+    pub fn turn(&self) -> SeqTurn {
+        SeqTurn {
+            inner: Some(self.inner.clone()),
+            seq: self.seq,
+            timer: false
+        }
     }
 
     /// Verify that this handle was called in the correct order
-    pub fn verify(&self, desc: &str) {
-        self.inner.verify(self.seq, desc);
+    pub fn verify(&self, desc: &str) -> Result<(), String> {
+        self.inner.verify(self.seq, desc)
+    }
+}
+
+/// A future that resolves when a member of a rendezvous [`Sequence`] may be
+/// called.  Async mock methods await it before calling their expectations, so
+/// a call that arrives too early doesn't block the executor.  Static methods
+/// [`wait`](SeqTurn::wait) for it before locking their expectations, so the
+/// call that should come first isn't locked out.
+#[doc(hidden)]
+#[derive(Default)]
+#[must_use = "futures do nothing unless polled"]
+pub struct SeqTurn {
+    /// `None` if the expectation doesn't belong to any Sequence
+    inner: Option<Arc<SeqInner>>,
+    seq: usize,
+    /// Has the [`SeqTimer`] been asked to wake this future at the deadline?
+    timer: bool
+}
+
+impl Debug for SeqTurn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("SeqTurn").field("seq", &self.seq).finish()
+    }
+}
+
+impl SeqTurn {
+    /// Block until the member may be called, or the rendezvous Sequence's
+    /// timeout expires.
+    pub fn wait(mut self) {
+        let inner = match self.inner.take() {
+            Some(inner) => inner,
+            None => return
+        };
+        let timeout = match inner.timeout {
+            Some(timeout) => timeout,
+            None => return
+        };
+        let seq = self.seq;
+        let mut state = inner.state.lock().unwrap();
+        // Share the deadline with SeqInner::verify, so the call won't wait
+        // all over again once it's made.
+        let deadline = *state.steps[seq].deadline
+            .get_or_insert_with(|| Instant::now() + timeout);
+        while state.expected(seq).is_some() && seq >= state.level {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            state = inner.changed.wait_timeout(state, deadline - now)
+                .unwrap().0;
+        }
+    }
+}
+
+impl Future for SeqTurn {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let (inner, timeout) = match &self.inner {
+            Some(inner) => match inner.timeout {
+                Some(timeout) => (inner.clone(), timeout),
+                None => return Poll::Ready(())
+            },
+            None => return Poll::Ready(())
+        };
+        let seq = self.seq;
+        let mut state = inner.state.lock().unwrap();
+        // A call that's too late will never become valid, so don't wait
+        if state.expected(seq).is_none() || seq < state.level {
+            // Any deadline belongs to the call that will now verify it
+            return Poll::Ready(());
+        }
+        // Share the deadline with SeqInner::verify, so the call won't wait
+        // all over again once it's made.
+        let deadline = *state.steps[seq].deadline
+            .get_or_insert_with(|| Instant::now() + timeout);
+        let now = Instant::now();
+        if now >= deadline {
+            // The call wasn't cancelled, so keep the deadline for it
+            drop(state);
+            self.inner = None;
+            return Poll::Ready(());
+        }
+        state.wakers.push(cx.waker().clone());
+        drop(state);
+        if !self.timer {
+            self.timer = true;
+            SeqTimer::wake_at(deadline, &inner);
+        }
+        Poll::Pending
+    }
+}
+
+lazy_static! {
+    static ref SEQ_TIMER: (Mutex<SeqTimer>, Condvar) = Default::default();
+}
+
+/// Wakes pending [`SeqTurn`]s once their deadlines pass.  A single thread
+/// serves every rendezvous [`Sequence`], however many futures are waiting.
+#[derive(Default)]
+struct SeqTimer {
+    /// Has the timer thread been started?
+    started: bool,
+    /// Sequences with pending futures, and when to wake them
+    deadlines: Vec<(Instant, Weak<SeqInner>)>
+}
+
+impl SeqTimer {
+    /// Wake every task waiting on `inner` at `deadline`
+    fn wake_at(deadline: Instant, inner: &Arc<SeqInner>) {
+        let (timer, changed) = &*SEQ_TIMER;
+        let mut timer = timer.lock().unwrap();
+        timer.deadlines.push((deadline, Arc::downgrade(inner)));
+        if !timer.started {
+            timer.started = true;
+            thread::spawn(SeqTimer::run);
+        }
+        changed.notify_one();
+    }
+
+    fn run() {
+        let (timer, changed) = &*SEQ_TIMER;
+        let mut guard = timer.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            let mut due = Vec::new();
+            guard.deadlines.retain(|(deadline, inner)| {
+                if *deadline <= now {
+                    due.push(inner.clone());
+                }
+                *deadline > now
+            });
+            if !due.is_empty() {
+                // Don't hold the timer's lock while taking a Sequence's
+                drop(guard);
+                for inner in due.iter().filter_map(Weak::upgrade) {
+                    let wakers = match inner.state.lock() {
+                        Ok(mut state) => mem::take(&mut state.wakers),
+                        Err(_) => continue
+                    };
+                    for waker in wakers {
+                        waker.wake();
+                    }
+                }
+                guard = timer.lock().unwrap();
+                continue;
+            }
+            guard = match guard.deadlines.iter().map(|(d, _)| *d).min() {
+                Some(next) => changed.wait_timeout(guard, next - now)
+                    .unwrap().0,
+                None => changed.wait(guard).unwrap()
+            };
+        }
+    }
+}
+
+impl Drop for SeqTurn {
+    fn drop(&mut self) {
+        if let Some(inner) = &self.inner {
+            if let Ok(mut state) = inner.state.lock() {
+                // If the call was cancelled, a later one shouldn't inherit the
+                // deadline.
+                if state.expected(self.seq).is_some() {
+                    state.steps[self.seq].deadline = None;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for SeqHandle {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.inner.state.lock() {
+            state.steps[self.seq].dropped = true;
+        }
     }
 }
 
 #[derive(Default)]
 struct SeqInner {
-    satisfaction_level: AtomicUsize,
+    state: Mutex<SeqState>,
+    /// Signalled whenever a member of the sequence changes
+    changed: Condvar,
+    /// How long an early call may wait for its turn.  `None` unless the
+    /// sequence was created by [`Sequence::rendezvous`].
+    timeout: Option<Duration>
 }
 
-impl SeqInner {
-    /// Record the call identified by `seq` as fully satisfied.
-    fn satisfy(&self, seq: usize) {
-        let old_sl = self.satisfaction_level.fetch_add(1, Ordering::Relaxed);
-        assert_eq!(old_sl, seq, "Method sequence violation.  Was an already-satisfied method called another time?");
+#[derive(Default)]
+struct SeqState {
+    /// The member of the sequence that is currently expected to be called
+    level: usize,
+    steps: Vec<SeqStep>,
+    /// Tasks waiting on a [`SeqTurn`]
+    wakers: Vec<Waker>
+}
+
+impl SeqState {
+    /// If the call identified by `seq` would violate the sequence, return the
+    /// member that should be called instead.
+    fn expected(&self, seq: usize) -> Option<usize> {
+        if seq < self.level {
+            Some(self.level)
+        } else {
+            (self.level..seq).find(|i| !self.steps[*i].satisfied)
+        }
     }
+}
+
+/// One member of a [`Sequence`]
+#[derive(Default)]
+struct SeqStep {
+    /// Describes the expectation, like `MockFoo::foo: Expectation(<anything>)`
+    desc: String,
+    /// Where the expectation was created
+    location: Option<&'static Location<'static>>,
+    /// Has the expectation been called at least as many times as it requires?
+    satisfied: bool,
+    /// Has the expectation been dropped?  If so, it reported its own failures.
+    dropped: bool,
+    /// Has the Sequence reported the expectation as unsatisfied?
+    reported: bool,
+    /// When a pending [`SeqTurn`] for this member will give up waiting
+    deadline: Option<Instant>
+}
 
-    /// Verify that the call identified by `seq` was called in the correct order
-    fn verify(&self, seq: usize, desc: &str) {
-        assert_eq!(seq, self.satisfaction_level.load(Ordering::Relaxed),
-            "{}: Method sequence violation", desc)
+impl SeqStep {
+    /// Describe the step for failure messages, including where it was set.
+    fn what(&self) -> String {
+        format!("{}{}", self.desc, whence(self.location, None))
+    }
+}
+
+impl SeqInner {
+    /// Verify that the call identified by `seq` was called in the correct
+    /// order.  The sequence may move past members that are already satisfied.
+    /// If it's a rendezvous sequence, an early call will wait for its turn.
+    fn verify(&self, seq: usize, desc: &str) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        let mut expected = state.expected(seq);
+        let deadline = state.steps[seq].deadline.take();
+        if let Some(timeout) = self.timeout {
+            let deadline = deadline.unwrap_or_else(|| Instant::now() + timeout);
+            // A call that's too late will never become valid, so only wait
+            // for calls that are too early.
+            while expected.is_some() && seq >= state.level {
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+                state = self.changed.wait_timeout(state, deadline - now)
+                    .unwrap().0;
+                expected = state.expected(seq);
+            }
+        }
+        match expected {
+            None => {
+                state.level = seq;
+                Ok(())
+            },
+            Some(i) => {
+                let waited = match self.timeout {
+                    Some(timeout) if seq >= state.level =>
+                        format!(", even after waiting {:?}", timeout),
+                    _ => String::new()
+                };
+                Err(format!(
+                    "{}: Method sequence violation.  Expected {} next, but got {}{}",
+                    desc, state.steps[i].what(), state.steps[seq].desc, waited))
+            }
+        }
     }
 }
 
 /// Used to enforce that mock calls must happen in the sequence specified.
 ///
-/// Each expectation must expect to be called a fixed number of times.  Once
-/// satisfied, the next expectation in the sequence will expect to be called.
+/// Each expectation in the sequence must be called at least as many times as
+/// it requires before the next one may be called.  An expectation with a range
+/// of call counts, like `times(1..5)`, may keep being called until the next
+/// expectation in the sequence is called.  After that, it may not be called
+/// again.
+///
+/// Each expectation checks its own call count when dropped.  To check that the
+/// whole sequence was completed before then, use
+/// [`verify_complete`](Sequence::verify_complete) or
+/// [`verify_on_drop`](Sequence::verify_on_drop).
 ///
 /// # Examples
 /// ```
@@ -1702,11 +2944,12 @@ impl SeqInner {
 ///
 /// mock0.foo();
 /// mock1.bar();
+/// seq.verify_complete().unwrap();
 /// ```
 ///
-/// It is an error to add an expectation to a `Sequence` if its call count is
-/// unspecified.
-/// ```should_panic(expected = "with an exact call count")
+/// It is an error to add an expectation to a `Sequence` if its call count has
+/// no upper bound.
+/// ```should_panic(expected = "with a bounded call count")
 /// # use mockall::*;
 /// #[automock]
 /// trait Foo {
@@ -1719,24 +2962,1255 @@ impl SeqInner {
 ///     .returning(|| ())
 ///     .in_sequence(&mut seq);  // panics!
 /// ```
-#[derive(Default)]
-pub struct Sequence {
+pub struct Sequence<K = Immediate> {
     inner: Arc<SeqInner>,
     next_seq: usize,
+    reporter: Reporter,
+    verify_on_drop: bool,
+    _kind: PhantomData<fn() -> K>
 }
 
+/// Marks a [`Sequence`] whose calls fail as soon as they arrive out of order.
+#[derive(Debug)]
+pub enum Immediate {}
+
+/// Marks a [`Sequence`] created by [`Sequence::rendezvous`], whose early calls
+/// wait for their turn.
+#[derive(Debug)]
+pub enum Rendezvous {}
+
 impl Sequence {
     /// Create a new empty [`Sequence`]
     pub fn new() -> Self {
-        Self::default()
+        Sequence {
+            inner: Arc::default(),
+            next_seq: 0,
+            reporter: Reporter::current(),
+            verify_on_drop: false,
+            _kind: PhantomData
+        }
+    }
+}
+
+impl Sequence<Rendezvous> {
+    /// Create a new empty [`Sequence`] whose members wait for their turn.
+    ///
+    /// A call that arrives before the earlier members of the sequence have
+    /// been satisfied will wait, for up to `timeout`, for other threads to
+    /// satisfy them, instead of failing immediately.  That's useful when the
+    /// code under test orders its calls by design, but its threads race to
+    /// get started.  `async` methods wait without blocking their executor.
+    ///
+    /// A call that arrives after its turn has passed still fails immediately.
+    ///
+    /// Static methods wait without holding the lock on their expectations, so
+    /// the call they're waiting for can still proceed.  However, a static
+    /// method with closure or `#[concretize]` arguments can't match its
+    /// expectation until it holds that lock, so its expectations only accept
+    /// a plain [`Sequence`].
+    /// ```compile_fail
+    /// # use mockall::*;
+    /// # use std::time::Duration;
+    /// #[automock]
+    /// trait Foo {
+    ///     fn foo<F: Fn(u32) + 'static>(f: F);
+    /// }
+    /// let ctx = MockFoo::foo_context();
+    /// let mut seq = Sequence::rendezvous(Duration::from_secs(60));
+    /// ctx.expect()
+    ///     .times(1)
+    ///     .in_sequence(&mut seq)   // Won't compile
+    ///     .return_const(());
+    /// ```
+    ///
+    /// # Examples
+    /// ```
+    /// # use mockall::*;
+    /// # use std::{sync::Arc, thread, time::Duration};
+    /// #[automock]
+    /// trait Foo {
+    ///     fn foo(&self, x: u32);
+    /// }
+    /// let mut seq = Sequence::rendezvous(Duration::from_secs(60));
+    /// let mut mock = MockFoo::new();
+    /// mock.expect_foo()
+    ///     .with(predicate::eq(1))
+    ///     .times(1)
+    ///     .in_sequence(&mut seq)
+    ///     .return_const(());
+    /// mock.expect_foo()
+    ///     .with(predicate::eq(2))
+    ///     .times(1)
+    ///     .in_sequence(&mut seq)
+    ///     .return_const(());
+    ///
+    /// let mock = Arc::new(mock);
+    /// let mock2 = mock.clone();
+    /// // This call will wait until the other thread calls foo(1)
+    /// let t = thread::spawn(move || mock2.foo(2));
+    /// mock.foo(1);
+    /// t.join().unwrap();
+    /// ```
+    pub fn rendezvous(timeout: Duration) -> Self {
+        let inner = SeqInner {
+            timeout: Some(timeout),
+            .. SeqInner::default()
+        };
+        Sequence {
+            inner: Arc::new(inner),
+            next_seq: 0,
+            reporter: Reporter::current(),
+            verify_on_drop: false,
+            _kind: PhantomData
+        }
     }
+}
+
+impl<K> Sequence<K> {
 
     /// Not for public consumption, but it must be public so the generated code
     /// can call it.
     #[doc(hidden)]
     pub fn next_handle(&mut self) -> SeqHandle {
+        self.inner.state.lock().unwrap().steps.push(SeqStep::default());
         let handle = SeqHandle{inner: self.inner.clone(), seq: self.next_seq};
         self.next_seq += 1;
         handle
     }
+
+    /// Check that every expectation in the sequence has been called at least
+    /// as many times as it requires.  If not, return a failure for each one
+    /// that hasn't, in sequence order.
+    pub fn verify_complete(&self) -> Result<(), MockErrors> {
+        let state = self.inner.state.lock().unwrap();
+        let errors = state.steps.iter()
+            .filter(|step| !step.satisfied)
+            .map(|step| MockError(Self::incomplete(step)))
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(MockErrors(errors))
+        }
+    }
+
+    /// Check, when the `Sequence` is dropped, that every expectation in it has
+    /// been called at least as many times as it requires, like
+    /// [`verify_complete`](Sequence::verify_complete) does.
+    ///
+    /// That catches unfinished sequences even if their mock objects live on,
+    /// for example in another thread.  But the `Sequence` must then outlive
+    /// every call in it, so it can't be created by a helper function that only
+    /// returns the mock object.
+    ///
+    /// # Examples
+    /// ```should_panic
+    /// # use mockall::*;
+    /// #[automock]
+    /// trait Foo {
+    ///     fn foo(&self);
+    /// }
+    /// let mut mock = MockFoo::new();
+    /// {
+    ///     let mut seq = Sequence::new();
+    ///     seq.verify_on_drop();
+    ///     mock.expect_foo()
+    ///         .times(1)
+    ///         .in_sequence(&mut seq)
+    ///         .return_const(());
+    /// }   // Panics here, because foo was never called
+    /// ```
+    pub fn verify_on_drop(&mut self) {
+        self.verify_on_drop = true;
+    }
+
+    fn incomplete(step: &SeqStep) -> String {
+        format!("Sequence incomplete: {} was not satisfied{}", step.desc,
+            whence(step.location, None))
+    }
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> Drop for Sequence<K> {
+    fn drop(&mut self) {
+        if !self.verify_on_drop || thread::panicking() {
+            return;
+        }
+        // Expectations that were already dropped reported their own failures.
+        let msgs = {
+            let mut state = self.inner.state.lock().unwrap();
+            state.steps.iter_mut()
+                .filter(|step| !step.satisfied && !step.dropped)
+                .map(|step| {
+                    step.reported = true;
+                    Self::incomplete(step)
+                }).collect::<Vec<_>>()
+        };
+        for msg in msgs {
+            self.reporter.fail(msg);
+        }
+    }
+}
+
+struct OrderNode {
+    /// Describes the expectation, for failure messages
+    what: String,
+    /// Has the expectation been called at least as many times as it requires?
+    satisfied: AtomicBool,
+    /// Has an expectation that must follow this one already been called?
+    closed: AtomicBool,
+    /// Expectations that must be satisfied before this one may be called
+    predecessors: Mutex<Vec<ExpectationHandle>>
+}
+
+/// Identifies an expectation, so other expectations can be ordered after it.
+///
+/// Returned by an expectation's `handle` method, and used by other
+/// expectations' `after` methods.  Unlike a [`Sequence`], which imposes a total
+/// order, `after` constraints form a partial order.  An expectation may only
+/// be called once every expectation that it follows has been called at least
+/// as many times as it requires.  And once it has been called, the
+/// expectations that it follows may not be called again.
+///
+/// # Examples
+/// ```
+/// # use mockall::*;
+/// #[automock]
+/// trait Foo {
+///     fn open(&self);
+///     fn read(&self, x: u32);
+///     fn close(&self);
+/// }
+///
+/// let mut mock = MockFoo::new();
+/// let open = mock.expect_open()
+///     .times(1)
+///     .return_const(())
+///     .handle();
+/// let read0 = mock.expect_read()
+///     .with(predicate::eq(0))
+///     .times(1)
+///     .after(&open)
+///     .return_const(())
+///     .handle();
+/// let read1 = mock.expect_read()
+///     .with(predicate::eq(1))
+///     .times(1)
+///     .after(&open)
+///     .return_const(())
+///     .handle();
+/// mock.expect_close()
+///     .times(1)
+///     .after(&read0)
+///     .after(&read1)
+///     .return_const(());
+///
+/// // The reads may happen in either order, but only after the open
+/// mock.open();
+/// mock.read(1);
+/// mock.read(0);
+/// mock.close();
+/// ```
+#[derive(Clone)]
+pub struct ExpectationHandle(Arc<OrderNode>);
+
+impl ExpectationHandle {
+    #[doc(hidden)]
+    pub fn new(what: String) -> Self {
+        ExpectationHandle(Arc::new(OrderNode {
+            what,
+            satisfied: AtomicBool::new(false),
+            closed: AtomicBool::new(false),
+            predecessors: Mutex::new(Vec::new())
+        }))
+    }
+
+    /// Require the expectation `before` to be satisfied first.
+    #[doc(hidden)]
+    pub fn after(&self, before: &ExpectationHandle) {
+        self.0.predecessors.lock().unwrap().push(before.clone());
+    }
+
+    /// Record whether this expectation has been called at least as many
+    /// times as it requires.
+    #[doc(hidden)]
+    pub fn set_satisfied(&self, satisfied: bool) {
+        self.0.satisfied.store(satisfied, Ordering::Relaxed);
+    }
+
+    /// Verify that this expectation may be called now, and close every
+    /// expectation that must precede it.
+    #[doc(hidden)]
+    pub fn verify(&self, desc: &str) -> Result<(), String> {
+        let predecessors = self.0.predecessors.lock().unwrap();
+        if let Some(p) = predecessors.iter()
+            .find(|p| !p.0.satisfied.load(Ordering::Relaxed))
+        {
+            return Err(format!(
+                "{}: Method order violation.  It must follow {}",
+                desc, p.0.what));
+        }
+        if self.0.closed.load(Ordering::Relaxed) {
+            return Err(format!(
+                "{}: Method order violation.  An expectation that must follow it was already called",
+                desc));
+        }
+        for p in predecessors.iter() {
+            p.0.closed.store(true, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+}
+
+impl Debug for ExpectationHandle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_tuple("ExpectationHandle")
+            .field(&self.0.what)
+            .finish()
+    }
+}
+
+/// Tracks the state of a protocol, so expectations can depend on it.
+///
+/// An expectation tied to a state with `when_state` will only match calls
+/// while the machine is in that state.  An expectation with `then_state` will
+/// move the machine to a new state whenever it's called.  That way, the same
+/// method can behave differently in each phase of a protocol, without a
+/// `returning` closure that tracks the phase by hand.
+///
+/// Cloning a `StateMachine` yields another reference to the same machine.
+///
+/// # Examples
+/// ```
+/// # use mockall::*;
+/// #[derive(Clone, Debug, PartialEq)]
+/// enum State { Idle, Connected, Closed }
+///
+/// #[automock]
+/// trait Conn {
+///     fn connect(&self) -> bool;
+///     fn query(&self) -> Option<u32>;
+///     fn close(&self);
+/// }
+///
+/// let machine = StateMachine::new(State::Idle);
+/// let mut mock = MockConn::new();
+/// mock.expect_connect()
+///     .when_state(&machine, State::Idle)
+///     .then_state(&machine, State::Connected)
+///     .return_const(true);
+/// mock.expect_query()
+///     .when_state(&machine, State::Connected)
+///     .return_const(Some(42));
+/// mock.expect_query()
+///     .when_state(&machine, State::Closed)
+///     .return_const(None);
+/// mock.expect_close()
+///     .when_state(&machine, State::Connected)
+///     .then_state(&machine, State::Closed)
+///     .return_const(());
+///
+/// assert!(mock.connect());
+/// assert_eq!(mock.query(), Some(42));
+/// mock.close();
+/// assert_eq!(mock.query(), None);
+/// assert_eq!(machine.state(), State::Closed);
+/// ```
+pub struct StateMachine<S> {
+    state: Arc<Mutex<S>>
+}
+
+impl<S> StateMachine<S>
+    where S: Clone + Debug + PartialEq + Send + Sync + 'static
+{
+    /// Create a new machine in the `initial` state.
+    pub fn new(initial: S) -> Self {
+        StateMachine{state: Arc::new(Mutex::new(initial))}
+    }
+
+    /// Return the machine's current state.
+    pub fn state(&self) -> S {
+        self.state.lock().unwrap().clone()
+    }
+
+    /// Move the machine to `state`, regardless of its current state.
+    pub fn set_state(&self, state: S) {
+        *self.state.lock().unwrap() = state;
+    }
+
+    /// Not for public consumption, but it must be public so the generated code
+    /// can call it.
+    #[doc(hidden)]
+    pub fn guard(&self, state: S) -> StateGuard {
+        let current = self.state.clone();
+        StateGuard(Box::new(move || {
+            let current = current.lock().unwrap();
+            if *current == state {
+                Ok(())
+            } else {
+                Err(format!(
+                    "it's only valid in state {:?}, and the state is {:?}",
+                    state, *current))
+            }
+        }))
+    }
+
+    /// Not for public consumption, but it must be public so the generated code
+    /// can call it.
+    #[doc(hidden)]
+    pub fn transition(&self, state: S) -> StateTransition {
+        let current = self.state.clone();
+        StateTransition(Box::new(move || {
+            *current.lock().unwrap() = state.clone();
+        }))
+    }
+}
+
+impl<S> Clone for StateMachine<S> {
+    fn clone(&self) -> Self {
+        StateMachine{state: self.state.clone()}
+    }
+}
+
+impl<S: Debug> Debug for StateMachine<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_tuple("StateMachine")
+            .field(&*self.state.lock().unwrap())
+            .finish()
+    }
+}
+
+/// Only allows an expectation to match in one state of a [`StateMachine`]
+#[doc(hidden)]
+pub struct StateGuard(Box<dyn Fn() -> Result<(), String> + Send + Sync>);
+
+impl StateGuard {
+    /// Is the machine in the required state?  If not, explain why not.
+    pub fn check(&self) -> Result<(), String> {
+        (self.0)()
+    }
+}
+
+/// Moves a [`StateMachine`] to a new state when an expectation is called
+#[doc(hidden)]
+pub struct StateTransition(Box<dyn Fn() + Send + Sync>);
+
+impl StateTransition {
+    /// Move the machine to the new state
+    pub fn apply(&self) {
+        (self.0)()
+    }
+}
+
+/// Stores values computed from a mock method's arguments, for assertions after
+/// the call.
+///
+/// Give it to an expectation's `capture` method, along with a function that
+/// converts the arguments into the value to store.  That function can clone
+/// referenced arguments, or extract whatever it needs from arguments that
+/// can't be cloned, like trait objects.  Clones of a `Captor` share the same
+/// values.
+///
+/// # Examples
+///
+/// ```
+/// # use mockall::*;
+/// #[automock]
+/// trait Db {
+///     fn insert(&self, key: &str, value: u32);
+/// }
+///
+/// let keys = Captor::new();
+/// let mut mock = MockDb::new();
+/// mock.expect_insert()
+///     .capture(&keys, |key, _value| key.to_owned())
+///     .return_const(());
+///
+/// mock.insert("foo", 1);
+/// mock.insert("bar", 2);
+/// assert_eq!(keys.values(), ["foo", "bar"]);
+/// ```
+pub struct Captor<T> {
+    values: Arc<Mutex<Vec<T>>>
+}
+
+impl<T> Captor<T> {
+    /// Create a new `Captor` with no values.
+    pub fn new() -> Self {
+        Captor{values: Arc::new(Mutex::new(Vec::new()))}
+    }
+
+    /// Has nothing been captured yet?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many values have been captured?
+    pub fn len(&self) -> usize {
+        self.values.lock().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    /// Store another value.  Normally called by the mock method.
+    pub fn push(&self, value: T) {
+        self.values.lock().unwrap_or_else(PoisonError::into_inner).push(value);
+    }
+
+    /// Remove and return every captured value, oldest first.
+    pub fn take(&self) -> Vec<T> {
+        mem::take(&mut *self.values.lock()
+            .unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl<T: Clone> Captor<T> {
+    /// Return the most recently captured value, if any.
+    pub fn last(&self) -> Option<T> {
+        self.values.lock().unwrap_or_else(PoisonError::into_inner)
+            .last()
+            .cloned()
+    }
+
+    /// Return every captured value, oldest first.
+    pub fn values(&self) -> Vec<T> {
+        self.values.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+impl<T> Clone for Captor<T> {
+    fn clone(&self) -> Self {
+        Captor{values: self.values.clone()}
+    }
+}
+
+impl<T> Default for Captor<T> {
+    fn default() -> Self {
+        Captor::new()
+    }
+}
+
+impl<T: Debug> Debug for Captor<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_tuple("Captor")
+            .field(&*self.values.lock()
+                .unwrap_or_else(PoisonError::into_inner))
+            .finish()
+    }
+}
+
+/// Identifies a mock's static lock: the module that defines the mock, and the
+/// mock struct's name, or `""` for a mocked module.
+type StaticLockName = (&'static str, &'static str);
+
+/// Which thread holds each mock's static lock.
+#[derive(Default)]
+struct StaticLocks {
+    /// The owning thread and recursion depth of every held lock
+    held: HashMap<StaticLockName, (thread::ThreadId, usize)>
+}
+
+/// Holds a mock's static lock until dropped.  See [`lock_statics`].
+#[doc(hidden)]
+pub struct StaticGuard {
+    /// The lock's name, or `None` for thread-local expectations, which don't
+    /// need any lock.
+    name: Option<StaticLockName>
+}
+
+impl Drop for StaticGuard {
+    fn drop(&mut self) {
+        let name = match self.name {
+            Some(name) => name,
+            None => return
+        };
+        let (mutex, cvar) = &*STATIC_LOCKS;
+        let mut locks = mutex.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Entry::Occupied(mut e) = locks.held.entry(name) {
+            e.get_mut().1 -= 1;
+            if e.get().1 == 0 {
+                e.remove();
+                cvar.notify_all();
+            }
+        }
+    }
+}
+
+/// Acquire the lock that serializes every test using a mock's static methods.
+///
+/// `module` is the `module_path!()` of one of the mock's private modules,
+/// which is nested `levels` deep within the module that defines the mock.
+/// `mock` is the mock struct's name, or `""` for a mocked module.
+///
+/// The lock is reentrant, so one thread may hold several `Context`s for the
+/// same mock at once.  Other threads block until they're all dropped.  So do
+/// threads that create the `Context`s of different mocks in different orders,
+/// just like with any other pair of locks.
+#[doc(hidden)]
+pub fn lock_statics(module: &'static str, levels: usize, mock: &'static str)
+    -> StaticGuard
+{
+    let mut module = module;
+    for _ in 0..levels {
+        module = module.rsplitn(2, "::").nth(1).unwrap_or("");
+    }
+    let name = (module, mock);
+    let me = thread::current().id();
+    let (mutex, cvar) = &*STATIC_LOCKS;
+    let mut locks = mutex.lock().unwrap_or_else(PoisonError::into_inner);
+    loop {
+        match locks.held.get_mut(&name) {
+            None => {
+                locks.held.insert(name, (me, 1));
+                break;
+            },
+            Some((owner, depth)) if *owner == me => {
+                *depth += 1;
+                break;
+            },
+            Some(_) => ()
+        }
+        locks = cvar.wait(locks).unwrap_or_else(PoisonError::into_inner);
+    }
+    StaticGuard { name: Some(name) }
+}
+
+/// A guard for a `Context` whose expectations are thread-local.  It doesn't
+/// lock anything.
+#[doc(hidden)]
+pub fn thread_local_statics() -> StaticGuard {
+    StaticGuard { name: None }
+}
+
+type FailureHandler = Arc<dyn Fn(&MockError) + Send + Sync>;
+
+lazy_static! {
+    /// Counts every call to every mock method, so
+    /// [`wait_satisfied`](fn.wait_satisfied.html) can sleep until something
+    /// changes.
+    static ref CALLS: (Mutex<u64>, Condvar) = (Mutex::new(0), Condvar::new());
+    /// Tasks waiting on a [`Satisfied`] future.
+    static ref WAKERS: Mutex<Vec<Waker>> = Mutex::new(Vec::new());
+    /// Serializes tests that use the same mock's static methods.  See
+    /// [`lock_statics`].
+    static ref STATIC_LOCKS: (Mutex<StaticLocks>, Condvar) =
+        Default::default();
+}
+
+thread_local! {
+    /// Failures recorded by the innermost [`Verifier`] on this thread, if any.
+    static VERIFIER: RefCell<Option<Arc<Mutex<Vec<MockError>>>>> =
+        RefCell::new(None);
+    /// The innermost handler installed by [`set_failure_handler`] on this
+    /// thread, if any.
+    static FAILURE_HANDLER: RefCell<Option<FailureHandler>> =
+        RefCell::new(None);
+}
+
+/// A single failure recorded by a [`Verifier`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MockError(String);
+
+impl MockError {
+    /// The same message that the mock would otherwise have panicked with.
+    pub fn message(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for MockError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        f.write_str(&self.0)
+    }
+}
+
+impl error::Error for MockError {}
+
+/// All of the failures recorded by a [`Verifier`], in the order they happened.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MockErrors(Vec<MockError>);
+
+impl MockErrors {
+    /// Are there no failures at all?
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over the failures, oldest first.
+    pub fn iter(&self) -> slice::Iter<'_, MockError> {
+        self.0.iter()
+    }
+
+    /// How many failures were recorded?
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl Display for MockErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{} mock failure(s)", self.0.len())?;
+        for e in self.0.iter() {
+            write!(f, "\n{}", e)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for MockErrors {}
+
+impl IntoIterator for MockErrors {
+    type Item = MockError;
+    type IntoIter = vec::IntoIter<MockError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a MockErrors {
+    type Item = &'a MockError;
+    type IntoIter = slice::Iter<'a, MockError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// Collects mock failures instead of panicking at the first one.
+///
+/// While a `Verifier` is alive, any expectation created on the same thread
+/// will record its failures in the `Verifier` rather than panicking.  That
+/// includes calls in excess of the expected count, calls out of sequence, and
+/// expectations that were never satisfied.  The failures are recorded even if
+/// the mock is later called from a different thread.  Calls that match no
+/// expectation at all still panic, because the mock has nothing to return, but
+/// they are recorded first so they won't be lost along with the panic of a
+/// worker thread.
+///
+/// A `Verifier` that still holds unreported failures when it is dropped will
+/// panic with all of them.  See also [`Collecting failures`](index.html#collecting-failures).
+///
+/// # Examples
+/// ```
+/// # use mockall::*;
+/// #[automock]
+/// trait Foo {
+///     fn foo(&self);
+///     fn bar(&self);
+/// }
+///
+/// let verifier = Verifier::new();
+/// let mut mock = MockFoo::new();
+/// mock.expect_foo()
+///     .times(1)
+///     .return_const(());
+/// mock.expect_bar()
+///     .times(1)
+///     .return_const(());
+/// mock.foo();
+/// mock.foo();     // Too many calls, but doesn't panic
+/// drop(mock);     // bar was never called, but doesn't panic either
+///
+/// let errors = verifier.verify().unwrap_err();
+/// assert_eq!(errors.len(), 2);
+/// ```
+pub struct Verifier {
+    errors: Arc<Mutex<Vec<MockError>>>,
+    prev: Option<Arc<Mutex<Vec<MockError>>>>,
+    // A Verifier is bound to the thread that created it.
+    _not_send: PhantomData<*const ()>
+}
+
+impl Verifier {
+    /// Create a new `Verifier` and start collecting this thread's failures
+    /// into it.
+    pub fn new() -> Self {
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let prev = VERIFIER.with(|v| v.replace(Some(errors.clone())));
+        Verifier {errors, prev, _not_send: PhantomData}
+    }
+
+    /// Report every failure recorded so far, and forget them.
+    ///
+    /// Expectations that were never satisfied are only recorded once they are
+    /// dropped, so drop the mock objects or call their `checkpoint` methods
+    /// first.
+    pub fn verify(&self) -> Result<(), MockErrors> {
+        let errors = std::mem::take(&mut *self.errors.lock().unwrap());
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(MockErrors(errors))
+        }
+    }
+}
+
+impl Default for Verifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Verifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Verifier")
+            .field("errors", &*self.errors.lock().unwrap())
+            .finish()
+    }
+}
+
+impl Drop for Verifier {
+    fn drop(&mut self) {
+        VERIFIER.with(|v| *v.borrow_mut() = self.prev.take());
+        if !thread::panicking() {
+            if let Err(errors) = self.verify() {
+                panic!("{}", errors);
+            }
+        }
+    }
+}
+
+/// Send every mock failure to `handler` instead of panicking, until the
+/// returned guard drops.
+///
+/// The handler receives sequence violations, calls in excess of an
+/// expectation's count, calls that match no expectation, and expectations that
+/// were never satisfied.  Like a [`Verifier`], it's scoped to the current
+/// thread: it handles the failures of expectations created on this thread
+/// while the guard lives, even if the mock is later called from a different
+/// thread.  So tests running in parallel can each install their own.  A
+/// `Verifier` takes precedence over it.
+///
+/// The handler may itself panic.  If it doesn't, then the mock will carry on
+/// as if nothing went wrong, except for failures that leave it with nothing to
+/// return, like a call that matches no expectation.  Those will still panic
+/// after calling the handler.
+///
+/// # Examples
+/// ```
+/// # use mockall::*;
+/// # use std::sync::{Arc, Mutex};
+/// #[automock]
+/// trait Foo {
+///     fn foo(&self);
+/// }
+///
+/// let log = Arc::new(Mutex::new(Vec::new()));
+/// let log2 = log.clone();
+/// let guard = set_failure_handler(
+///     move |e| log2.lock().unwrap().push(e.to_string()));
+///
+/// let mut mock = MockFoo::new();
+/// mock.expect_foo()
+///     .times(1)
+///     .return_const(());
+/// mock.foo();
+/// mock.foo();
+/// drop(guard);
+///
+/// assert!(log.lock().unwrap()[0].starts_with(
+///     "MockFoo::foo: Expectation(<anything>) called 2 times which is more than the expected 1"));
+/// ```
+pub fn set_failure_handler<F>(handler: F) -> FailureHandlerGuard
+    where F: Fn(&MockError) + Send + Sync + 'static
+{
+    let prev = FAILURE_HANDLER.with(|h| h.replace(Some(Arc::new(handler))));
+    FailureHandlerGuard {prev, _not_send: PhantomData}
+}
+
+/// Returned by [`set_failure_handler`].  When dropped, it reinstates the
+/// thread's previous failure handler, if any.
+#[must_use = "The failure handler is removed when the guard drops"]
+pub struct FailureHandlerGuard {
+    prev: Option<FailureHandler>,
+    // Like a Verifier, the guard is bound to the thread that created it.
+    _not_send: PhantomData<*const ()>
+}
+
+impl Debug for FailureHandlerGuard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("FailureHandlerGuard").finish()
+    }
+}
+
+impl Drop for FailureHandlerGuard {
+    fn drop(&mut self) {
+        FAILURE_HANDLER.with(|h| *h.borrow_mut() = self.prev.take());
+    }
+}
+
+/// Where an expectation sends its failures: to a [`Verifier`], to the handler
+/// installed by [`set_failure_handler`], or straight to `panic!`.
+#[doc(hidden)]
+#[derive(Clone, Default)]
+pub struct Reporter(Option<Sink>);
+
+/// Somewhere other than `panic!` to report failures
+#[derive(Clone)]
+enum Sink {
+    Verifier(Arc<Mutex<Vec<MockError>>>),
+    Handler(FailureHandler)
+}
+
+impl Reporter {
+    /// Report to this thread's current [`Verifier`] or failure handler, if
+    /// any.
+    pub fn current() -> Self {
+        Reporter(VERIFIER.with(|v| v.borrow().clone())
+            .map(Sink::Verifier)
+            .or_else(|| FAILURE_HANDLER.with(|h| h.borrow().clone())
+                .map(Sink::Handler)))
+    }
+
+    /// The first of `reporters` that reports to a [`Verifier`] or a failure
+    /// handler, or else the current one.
+    pub fn first_active<'a, I>(reporters: I) -> Self
+        where I: IntoIterator<Item=&'a Reporter>
+    {
+        reporters.into_iter()
+            .find(|r| r.0.is_some())
+            .cloned()
+            .unwrap_or_else(Reporter::current)
+    }
+
+    /// Report a failure that the mock can recover from.
+    #[track_caller]
+    pub fn fail(&self, msg: String) {
+        if !self.handle(MockError(msg.clone())) {
+            panic!("{}", msg)
+        }
+    }
+
+    /// Report a failure that the mock can't recover from.
+    #[track_caller]
+    pub fn fatal(&self, msg: String) -> ! {
+        self.handle(MockError(msg.clone()));
+        panic!("{}", msg)
+    }
+
+    /// Pass the failure to a [`Verifier`] or a failure handler, if there is
+    /// one.
+    fn handle(&self, e: MockError) -> bool {
+        match &self.0 {
+            Some(Sink::Verifier(errors)) => {
+                errors.lock().unwrap().push(e);
+                true
+            },
+            Some(Sink::Handler(handler)) => {
+                handler(&e);
+                true
+            },
+            None => false
+        }
+    }
+}
+
+/// Returned by a method's `Expectations` when no expectation matched a call.
+#[doc(hidden)]
+pub struct NoMatch {
+    reporter: Reporter,
+    why: String
+}
+
+impl NoMatch {
+    pub fn new(reporter: Reporter, why: String) -> Self {
+        NoMatch{reporter, why}
+    }
+
+    /// Report the failure, prefixed by `msg`, and panic.
+    #[track_caller]
+    pub fn fail(self, msg: String) -> ! {
+        self.reporter.fatal(format!("{}{}", msg, self.why))
+    }
+
+    /// If the mock object is nice, return a default value instead of failing.
+    #[track_caller]
+    pub fn fail_or_default<O, F>(self, flavor: Flavor, msg: String, default: F)
+        -> O
+        where F: FnOnce() -> Result<O, &'static str>
+    {
+        if flavor != Flavor::Nice {
+            self.fail(msg)
+        }
+        match default() {
+            Ok(o) => o,
+            Err(e) => self.fail(format!(
+                "{}, and the nice mock can't return a default value: {}",
+                msg, e))
+        }
+    }
+}
+
+/// How a mock object treats calls that its expectations don't cover.
+#[doc(hidden)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Flavor {
+    /// Created by `new`.  Calls that no expectation matches fail.
+    Default,
+    /// Created by `new_nice`.  Calls that no expectation matches return a
+    /// default value.
+    Nice,
+    /// Created by `new_strict`.  Calls may only be handled by expectations
+    /// with a call count.
+    Strict
+}
+
+impl Default for Flavor {
+    fn default() -> Self {
+        Flavor::Default
+    }
+}
+
+/// How many threads and futures are waiting for expectations to be satisfied.
+/// While there are none, [`notify_call`] doesn't need to lock anything.
+static WAITERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts as one of the [`WAITERS`] while alive.
+struct Waiting;
+
+impl Waiting {
+    fn new() -> Self {
+        WAITERS.fetch_add(1, Ordering::SeqCst);
+        // Pairs with the fence in notify_call.  Either that call sees this
+        // waiter, or this waiter's next check sees the call's effects.
+        atomic::fence(Ordering::SeqCst);
+        Waiting
+    }
+}
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        WAITERS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Wake everything that's waiting for expectations to be satisfied.  Called
+/// after every call to a mock method.
+#[doc(hidden)]
+pub fn notify_call() {
+    atomic::fence(Ordering::SeqCst);
+    if WAITERS.load(Ordering::SeqCst) == 0 {
+        return;
+    }
+    {
+        let mut calls = CALLS.0.lock().unwrap();
+        *calls = calls.wrapping_add(1);
+        CALLS.1.notify_all();
+    }
+    for waker in WAKERS.lock().unwrap().drain(..) {
+        waker.wake();
+    }
+}
+
+/// Block until `unsatisfied` returns nothing, or until `timeout` expires.
+#[doc(hidden)]
+pub fn wait_satisfied<F>(timeout: Duration, unsatisfied: F)
+    -> Result<(), MockErrors>
+    where F: Fn() -> Vec<String>
+{
+    let deadline = Instant::now() + timeout;
+    let _waiting = Waiting::new();
+    // Check while holding the lock, so no call can slip in between the check
+    // and the wait.
+    let mut calls = CALLS.0.lock().unwrap();
+    loop {
+        let pending = unsatisfied();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(MockErrors(pending.into_iter().map(MockError).collect()));
+        }
+        calls = CALLS.1.wait_timeout(calls, deadline - now).unwrap().0;
+    }
+}
+
+/// A future that resolves once every expectation of a mock object has been
+/// called at least as many times as it requires.
+///
+/// Returned by the mock object's `satisfied` method.  It has no timeout of its
+/// own, so combine it with your executor's, for example `tokio::time::timeout`.
+/// See also [`Waiting for expectations`](index.html#waiting-for-expectations).
+#[must_use = "futures do nothing unless polled"]
+pub struct Satisfied<'a> {
+    unsatisfied: Box<dyn Fn() -> Vec<String> + 'a>,
+    _waiting: Waiting
+}
+
+impl<'a> Satisfied<'a> {
+    #[doc(hidden)]
+    pub fn new<F: Fn() -> Vec<String> + 'a>(unsatisfied: F) -> Self {
+        Satisfied{unsatisfied: Box::new(unsatisfied), _waiting: Waiting::new()}
+    }
+}
+
+impl<'a> Debug for Satisfied<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Satisfied").finish()
+    }
+}
+
+impl<'a> Future for Satisfied<'a> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // Check while holding the lock, so no call can slip in between the
+        // check and registering the waker.
+        let mut wakers = WAKERS.lock().unwrap();
+        if (self.unsatisfied)().is_empty() {
+            Poll::Ready(())
+        } else {
+            wakers.push(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+struct HandleState<T> {
+    /// Values passed to `complete` that no future has returned yet
+    values: VecDeque<T>,
+    wakers: Vec<Waker>,
+    polled: bool,
+    cancelled: bool
+}
+
+/// Lets a test complete by hand the futures returned by a mock method.
+///
+/// Usually created by an expectation's `returning_handle` method.  Every
+/// future obtained from the same handle stays pending until the test calls
+/// [`complete`](FutureHandle::complete), so the test can check how the code
+/// under test behaves while the call is still in flight.
+///
+/// # Examples
+/// ```
+/// # use futures::FutureExt;
+/// # use mockall::*;
+/// let handle = FutureHandle::<u32>::new();
+/// let mut fut = handle.future();
+/// assert!(!handle.is_polled());
+/// assert_eq!(None, (&mut fut).now_or_never());
+/// assert!(handle.is_polled());
+/// handle.complete(42);
+/// assert_eq!(Some(42), fut.now_or_never());
+/// ```
+pub struct FutureHandle<T> {
+    state: Arc<Mutex<HandleState<T>>>
+}
+
+impl<T> FutureHandle<T> {
+    /// Create a handle with no pending futures.
+    pub fn new() -> Self {
+        let state = HandleState {
+            values: VecDeque::new(),
+            wakers: Vec::new(),
+            polled: false,
+            cancelled: false
+        };
+        FutureHandle{state: Arc::new(Mutex::new(state))}
+    }
+
+    /// Resolve a pending future with `value`.  Each value resolves a single
+    /// future.  If none is waiting, the next one to be polled will get it.
+    pub fn complete(&self, value: T) {
+        let mut state = self.state.lock().unwrap();
+        state.values.push_back(value);
+        for waker in state.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+
+    /// Create another future that will wait on this handle.
+    pub fn future(&self) -> PendingFuture<T> {
+        PendingFuture{state: self.state.clone(), done: false}
+    }
+
+    /// Was a future dropped before it resolved?
+    pub fn is_cancelled(&self) -> bool {
+        self.state.lock().unwrap().cancelled
+    }
+
+    /// Has any future been polled yet?
+    pub fn is_polled(&self) -> bool {
+        self.state.lock().unwrap().polled
+    }
+}
+
+impl<T, E> FutureHandle<Result<T, E>> {
+    /// Resolve a pending future with an error.
+    pub fn fail(&self, error: E) {
+        self.complete(Err(error))
+    }
+}
+
+impl<T> Clone for FutureHandle<T> {
+    fn clone(&self) -> Self {
+        FutureHandle{state: self.state.clone()}
+    }
+}
+
+impl<T> Debug for FutureHandle<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("FutureHandle")
+            .field("polled", &state.polled)
+            .field("cancelled", &state.cancelled)
+            .finish()
+    }
+}
+
+impl<T> Default for FutureHandle<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A future that stays pending until its [`FutureHandle`] completes it.
+#[must_use = "futures do nothing unless polled"]
+pub struct PendingFuture<T> {
+    state: Arc<Mutex<HandleState<T>>>,
+    done: bool
+}
+
+impl<T> Debug for PendingFuture<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("PendingFuture")
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl<T> Drop for PendingFuture<T> {
+    fn drop(&mut self) {
+        if !self.done {
+            if let Ok(mut state) = self.state.lock() {
+                state.cancelled = true;
+            }
+        }
+    }
+}
+
+impl<T> Future for PendingFuture<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let this = self.get_mut();
+        let mut state = this.state.lock().unwrap();
+        state.polled = true;
+        match state.values.pop_front() {
+            Some(value) => {
+                drop(state);
+                this.done = true;
+                Poll::Ready(value)
+            },
+            None => {
+                state.wakers.push(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
 }
@@ -0,0 +1,124 @@
+// vim: tw=80
+//! Expectations can be partially ordered with `after`
+#![deny(warnings)]
+
+mod common;
+
+use common::without_locations;
+use mockall::*;
+
+#[automock]
+pub trait Foo {
+    fn a(&self);
+    fn b(&self);
+    fn c(&self);
+    fn d(&self);
+    fn e();
+}
+
+/// A must come first, then B and C in any order, then D
+fn diamond(mock: &mut MockFoo) {
+    let a = mock.expect_a()
+        .times(1)
+        .return_const(())
+        .handle();
+    let b = mock.expect_b()
+        .times(1)
+        .after(&a)
+        .return_const(())
+        .handle();
+    let c = mock.expect_c()
+        .times(1)
+        .after(&a)
+        .return_const(())
+        .handle();
+    mock.expect_d()
+        .times(1)
+        .after(&b)
+        .after(&c)
+        .return_const(());
+}
+
+#[test]
+fn any_order() {
+    let mut mock = MockFoo::new();
+    diamond(&mut mock);
+    mock.a();
+    mock.c();
+    mock.b();
+    mock.d();
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::b(): Method order violation.  It must follow MockFoo::a")]
+fn too_early() {
+    let mut mock = MockFoo::new();
+    diamond(&mut mock);
+    mock.b();
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::d(): Method order violation.  It must follow MockFoo::c")]
+fn missing_predecessor() {
+    let mut mock = MockFoo::new();
+    diamond(&mut mock);
+    mock.a();
+    mock.b();
+    mock.d();
+}
+
+#[test]
+fn order_violation_location() {
+    let verifier = Verifier::new();
+    let mut mock = MockFoo::new();
+    let a = mock.expect_a()
+        .times(1)
+        .return_const(())
+        .handle();
+    mock.expect_b()
+        .times(1)
+        .after(&a)
+        .return_const(());
+    mock.b();
+    mock.a();
+    let errors = verifier.verify().unwrap_err();
+    assert_eq!(without_locations(errors.iter().next().unwrap().message()),
+        "MockFoo::b(): Method order violation.  It must follow MockFoo::a (set at HERE) (set at HERE, called at HERE)");
+}
+
+/// An expectation with a range of call counts may be called until one that
+/// follows it is called.
+#[test]
+#[should_panic(expected = "MockFoo::a(): Method order violation.  An expectation that must follow it was already called")]
+fn ranged() {
+    let mut mock = MockFoo::new();
+    let a = mock.expect_a()
+        .times(1..)
+        .return_const(())
+        .handle();
+    mock.expect_b()
+        .times(1)
+        .after(&a)
+        .return_const(());
+    mock.a();
+    mock.a();
+    mock.b();
+    mock.a();
+}
+
+/// Static methods can be ordered too, even relative to other mock objects
+#[test]
+fn static_method() {
+    let ctx = MockFoo::e_context();
+    let mut mock = MockFoo::new();
+    let a = mock.expect_a()
+        .times(1)
+        .return_const(())
+        .handle();
+    ctx.expect()
+        .times(1)
+        .after(&a)
+        .return_const(());
+    mock.a();
+    MockFoo::e();
+}
@@ -0,0 +1,144 @@
+// vim: tw=80
+//! Mock methods record the arguments of every call they receive
+#![deny(warnings)]
+
+use mockall::*;
+
+#[derive(Debug, PartialEq)]
+pub struct NonClone(u32);
+
+#[automock]
+pub trait Foo {
+    fn foo(&self, x: u32, y: &str) -> u32;
+    fn bar(&self, x: &NonClone);
+    fn baz(&self, x: &dyn std::fmt::Debug);
+    fn bean<T: 'static>(&self, t: T);
+    fn boom(x: &[u8]);
+}
+
+#[test]
+fn empty() {
+    let mock = MockFoo::new();
+    assert!(mock.calls_foo().is_empty());
+}
+
+#[test]
+fn clone() {
+    set_call_history_limit(1000);
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .returning(|x, _| x + 1);
+    mock.foo(1, "one");
+    mock.foo(2, "two");
+    let calls = mock.calls_foo();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].0, 1);
+    assert_eq!(calls[0].1, String::from("one"));
+    assert_eq!(calls[1].0.value(), Some(&2));
+    assert_eq!(calls[1].1.value().map(String::as_str), Some("two"));
+}
+
+/// Recorded arguments print the same as the originals
+#[test]
+fn debug() {
+    set_call_history_limit(1000);
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .return_const(0u32);
+    mock.expect_bar()
+        .return_const(());
+    mock.expect_baz()
+        .return_const(());
+    mock.foo(1, "one");
+    mock.bar(&NonClone(42));
+    mock.baz(&"forty-two");
+    let calls = mock.calls_foo();
+    assert_eq!(format!("{:?}", calls[0].0), "1");
+    assert_eq!(format!("{:?}", calls[0].1), "\"one\"");
+    assert_eq!(format!("{:?}", mock.calls_bar()[0].0), "NonClone(42)");
+    assert_eq!(format!("{:?}", mock.calls_baz()[0].0), "\"forty-two\"");
+}
+
+#[test]
+fn non_clone() {
+    set_call_history_limit(1000);
+    let mut mock = MockFoo::new();
+    mock.expect_bar()
+        .return_const(());
+    mock.bar(&NonClone(42));
+    let calls = mock.calls_bar();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].0.value(), None);
+}
+
+#[test]
+fn trait_object() {
+    set_call_history_limit(1000);
+    let mut mock = MockFoo::new();
+    mock.expect_baz()
+        .return_const(());
+    mock.baz(&42u32);
+    let calls = mock.calls_baz();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].0.value(), None);
+}
+
+#[test]
+fn generic_method() {
+    set_call_history_limit(1000);
+    let mut mock = MockFoo::new();
+    mock.expect_bean::<u32>()
+        .return_const(());
+    mock.expect_bean::<i16>()
+        .return_const(());
+    mock.bean(1u32);
+    mock.bean(-1i16);
+    assert_eq!(mock.calls_bean().len(), 2);
+}
+
+#[test]
+fn unmatched_calls_are_recorded() {
+    set_call_history_limit(1000);
+    let mock = MockFoo::new();
+    let r = std::panic::catch_unwind(
+        std::panic::AssertUnwindSafe(|| mock.foo(5, "five")));
+    assert!(r.is_err());
+    assert_eq!(mock.calls_foo()[0].0, 5);
+}
+
+#[test]
+fn survives_checkpoint() {
+    set_call_history_limit(1000);
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .return_const(0u32);
+    mock.foo(1, "one");
+    mock.checkpoint();
+    assert_eq!(mock.calls_foo().len(), 1);
+}
+
+#[test]
+fn static_method() {
+    set_call_history_limit(1000);
+    let ctx = MockFoo::boom_context();
+    ctx.expect()
+        .return_const(());
+    MockFoo::boom(&[1, 2, 3]);
+    let calls = ctx.calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].0, vec![1u8, 2, 3]);
+}
+
+/// Creating another Context doesn't discard the history, but dropping one does
+#[test]
+fn static_method_cleared_on_drop() {
+    set_call_history_limit(1000);
+    let ctx = MockFoo::boom_context();
+    ctx.expect()
+        .return_const(());
+    MockFoo::boom(&[1]);
+    let ctx2 = MockFoo::boom_context();
+    assert_eq!(ctx2.calls().len(), 1);
+    drop(ctx2);
+    assert!(ctx.calls().is_empty());
+}
@@ -0,0 +1,51 @@
+// vim: tw=80
+//! The call history records no more calls than the limit allows, and nothing
+//! at all by default
+#![deny(warnings)]
+
+use mockall::*;
+
+#[automock]
+pub trait Foo {
+    fn foo(&self, x: u32);
+    fn bar<T: 'static>(&self, t: T);
+    fn baz(x: u32);
+}
+
+// The limit is global, so set it in a single test
+#[test]
+fn limit() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .return_const(());
+    mock.foo(0);
+    assert!(mock.calls_foo().is_empty());
+
+    set_call_history_limit(2);
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .return_const(());
+    mock.expect_bar::<u32>()
+        .return_const(());
+    let ctx = MockFoo::baz_context();
+    ctx.expect()
+        .return_const(());
+    for i in 0..5 {
+        mock.foo(i);
+        mock.bar(i);
+        MockFoo::baz(i);
+    }
+    let calls = mock.calls_foo();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].0, 0);
+    assert_eq!(calls[1].0, 1);
+    assert_eq!(mock.calls_bar().len(), 2);
+    assert_eq!(ctx.calls().len(), 2);
+
+    set_call_history_limit(0);
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .return_const(());
+    mock.foo(0);
+    assert!(mock.calls_foo().is_empty());
+}
@@ -0,0 +1,146 @@
+// vim: tw=80
+//! Failure messages should show where the expectation was set, and where the
+//! mock was called.
+#![deny(warnings)]
+
+use mockall::*;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+#[automock]
+pub trait Foo {
+    fn foo(&self, x: u32) -> u32;
+    fn bar<T: 'static>(&self, t: T);
+    fn baz();
+    fn qux(&self) -> std::time::Instant;
+}
+
+/// The location of the next line, as a prefix of the `file:line:column` that
+/// failure messages print.
+macro_rules! next_line {
+    () => (format!("{}:{}:", file!(), line!() + 1))
+}
+
+/// Run `f`, which must panic, and return its panic message
+fn panic_message<F: FnOnce()>(f: F) -> String {
+    let e = catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+    e.downcast::<String>().map(|s| *s).unwrap()
+}
+
+/// Check that `msg` starts with `prefix`, and continues with `locations`,
+/// each followed by a column number, in order.
+fn assert_locations(msg: &str, prefix: &str, locations: &[(&str, &str)]) {
+    assert!(msg.starts_with(prefix), "{}", msg);
+    let mut rest = &msg[prefix.len()..];
+    for (label, loc) in locations {
+        let expected = format!("{}{}", label, loc);
+        assert!(rest.starts_with(&expected), "{}\nExpected {}", msg, expected);
+        rest = rest[expected.len()..]
+            .trim_start_matches(|c: char| c.is_ascii_digit());
+    }
+}
+
+#[test]
+fn checkpoint() {
+    let mut set = String::new();
+    let msg = panic_message(|| {
+        let mut mock = MockFoo::new();
+        set = next_line!();
+        mock.expect_foo()
+            .times(1)
+            .return_const(0u32);
+        mock.checkpoint();
+    });
+    assert_locations(&msg,
+        "MockFoo::foo: Expectation(<anything>) called 0 time(s) which is fewer than expected 1",
+        &[(" (set at ", &set)]);
+}
+
+#[test]
+fn generic_method() {
+    let (mut set, mut called) = (String::new(), String::new());
+    let msg = panic_message(|| {
+        let mut mock = MockFoo::new();
+        set = next_line!();
+        mock.expect_bar::<u32>()
+            .times(1)
+            .return_const(());
+        mock.bar(1u32);
+        called = next_line!();
+        mock.bar(1u32);
+    });
+    assert_locations(&msg,
+        "MockFoo::bar: Expectation(<anything>) called 2 times which is more than the expected 1",
+        &[(" (set at ", &set), (", called at ", &called)]);
+}
+
+#[test]
+fn no_match() {
+    let mut called = String::new();
+    let msg = panic_message(|| {
+        let mock = MockFoo::new();
+        called = next_line!();
+        mock.foo(5);
+    });
+    assert_locations(&msg, "MockFoo::foo(5): No matching expectation found",
+        &[(" (called at ", &called)]);
+}
+
+#[test]
+#[cfg_attr(feature = "nightly", ignore)]
+fn no_return_value() {
+    let (mut set, mut called) = (String::new(), String::new());
+    let msg = panic_message(|| {
+        let mut mock = MockFoo::new();
+        set = next_line!();
+        mock.expect_qux();
+        called = next_line!();
+        mock.qux();
+    });
+    assert_locations(&msg,
+        "MockFoo::qux: Expectation(<anything>) Can only return default values for types that impl std::Default.  For generic types, that requires the \"nightly\" feature",
+        &[(" (set at ", &set), (", called at ", &called)]);
+}
+
+#[test]
+fn sequence() {
+    let (mut set1, mut set5, mut called) =
+        (String::new(), String::new(), String::new());
+    let msg = panic_message(|| {
+        let mut seq = Sequence::new();
+        let mut mock = MockFoo::new();
+        set1 = next_line!();
+        mock.expect_foo()
+            .with(predicate::eq(1))
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_const(0u32);
+        set5 = next_line!();
+        mock.expect_foo()
+            .with(predicate::eq(5))
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_const(0u32);
+        called = next_line!();
+        mock.foo(5);
+    });
+    assert_locations(&msg,
+        "MockFoo::foo(5): Method sequence violation.  Expected MockFoo::foo: Expectation(var == 1)",
+        &[(" (set at ", &set1),
+          (") next, but got MockFoo::foo: Expectation(var == 5) (set at ", &set5),
+          (", called at ", &called)]);
+}
+
+#[test]
+fn static_method() {
+    let mut set = String::new();
+    let msg = panic_message(|| {
+        let ctx = MockFoo::baz_context();
+        set = next_line!();
+        ctx.expect()
+            .times(1)
+            .return_const(());
+    });
+    assert_locations(&msg,
+        "MockFoo::baz: Expectation(<anything>) called 0 time(s) which is fewer than expected 1",
+        &[(" (set at ", &set)]);
+}
@@ -0,0 +1,117 @@
+// vim: tw=80
+//! Captors store values computed from a mock method's arguments, for
+//! assertions after the call.
+#![deny(warnings)]
+
+use mockall::*;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Request {
+    pub path: String,
+    pub body: Vec<u8>
+}
+
+#[automock]
+pub trait Foo {
+    fn send(&self, req: Request) -> bool;
+    fn insert(&self, key: &str, value: &u32);
+    fn generic<T: Clone + 'static>(&self, t: T);
+    #[concretize]
+    fn open<P: AsRef<Path>>(&self, p: P);
+    fn create(name: &str) -> u32;
+}
+
+#[test]
+fn capturing() {
+    let mut count = 0;
+    let total = Captor::new();
+    let total2 = total.clone();
+    let mut mock = MockFoo::new();
+    mock.expect_insert()
+        .capturing(move |_, value| {
+            count += value;
+            total2.push(count);
+        })
+        .return_const(());
+    mock.insert("a", &1);
+    mock.insert("b", &2);
+    assert_eq!(total.last(), Some(3));
+}
+
+#[test]
+fn concretize() {
+    let paths = Captor::new();
+    let mut mock = MockFoo::new();
+    mock.expect_open()
+        .capture(&paths, |p| p.as_ref().to_owned())
+        .return_const(());
+    mock.open("/tmp");
+    mock.open(PathBuf::from("/usr"));
+    assert_eq!(paths.values(), [PathBuf::from("/tmp"), PathBuf::from("/usr")]);
+}
+
+#[test]
+fn generic_method() {
+    let captor = Captor::new();
+    let mut mock = MockFoo::new();
+    mock.expect_generic::<i16>()
+        .capture(&captor, |t| *t)
+        .return_const(());
+    mock.generic(-5i16);
+    assert_eq!(captor.values(), [-5]);
+}
+
+/// Only the expectation that handles the call captures its arguments
+#[test]
+fn only_matching() {
+    let captor = Captor::new();
+    let mut mock = MockFoo::new();
+    mock.expect_send()
+        .withf(|req| req.path == "/a")
+        .capture(&captor, |req| req.body.clone())
+        .return_const(true);
+    mock.expect_send()
+        .return_const(false);
+    assert!(!mock.send(Request{path: "/b".to_owned(), body: vec![2]}));
+    assert!(mock.send(Request{path: "/a".to_owned(), body: vec![1]}));
+    assert_eq!(captor.values(), [vec![1]]);
+}
+
+#[test]
+fn owned_arguments() {
+    let captor = Captor::new();
+    let mut mock = MockFoo::new();
+    mock.expect_send()
+        .capture(&captor, Request::clone)
+        .return_const(true);
+    let req = Request{path: "/x".to_owned(), body: vec![1, 2, 3]};
+    mock.send(req.clone());
+    assert_eq!(captor.len(), 1);
+    assert_eq!(captor.take(), [req]);
+    assert!(captor.is_empty());
+}
+
+#[test]
+fn reference_arguments() {
+    let captor = Captor::new();
+    let mut mock = MockFoo::new();
+    mock.expect_insert()
+        .capture(&captor, |key, value| (key.to_owned(), *value))
+        .return_const(());
+    mock.insert("foo", &1);
+    mock.insert("bar", &2);
+    assert_eq!(captor.values(),
+        [("foo".to_owned(), 1), ("bar".to_owned(), 2)]);
+}
+
+#[test]
+fn static_method() {
+    let names = Captor::new();
+    let ctx = MockFoo::create_context();
+    ctx.expect()
+        .capture(&names, |name| name.to_owned())
+        .return_const(0u32);
+    MockFoo::create("foo");
+    assert_eq!(names.values(), ["foo"]);
+}
@@ -0,0 +1,162 @@
+// vim: tw=80
+//! A failure handler receives every mock failure from its own thread's
+//! expectations
+#![deny(warnings)]
+
+mod common;
+
+use common::without_locations;
+use mockall::*;
+use std::sync::{Arc, Mutex, mpsc};
+
+#[automock]
+trait Foo {
+    fn foo(&self, x: u32) -> u32;
+    fn bar(&self);
+}
+
+/// Install a handler that logs every failure, for the duration of the closure
+fn with_log<F: FnOnce()>(f: F) -> Vec<String> {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let log2 = log.clone();
+    let guard = set_failure_handler(
+        move |e| log2.lock().unwrap().push(e.to_string()));
+    let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+    drop(guard);
+    r.unwrap();
+    Arc::try_unwrap(log).unwrap().into_inner().unwrap()
+}
+
+#[test]
+fn channel() {
+    let (tx, rx) = mpsc::channel();
+    let tx = Mutex::new(tx);
+    let _guard = set_failure_handler(
+        move |e| tx.lock().unwrap().send(e.clone()).unwrap());
+    let mut mock = MockFoo::new();
+    mock.expect_bar()
+        .times(1)
+        .return_const(());
+    drop(mock);
+    assert_eq!(without_locations(rx.recv().unwrap().message()),
+        "MockFoo::bar: Expectation(<anything>) called 0 time(s) which is fewer than expected 1 (set at HERE)");
+}
+
+/// Dropping the guard reinstates the previous handler
+#[test]
+fn nested() {
+    let outer = with_log(|| {
+        let inner = with_log(|| {
+            let mut mock = MockFoo::new();
+            mock.expect_bar()
+                .times(1)
+                .return_const(());
+        });
+        assert_eq!(inner.len(), 1);
+        let mut mock = MockFoo::new();
+        mock.expect_bar()
+            .times(2)
+            .return_const(());
+    });
+    assert_eq!(outer.len(), 1);
+    assert!(outer[0].contains("fewer than expected 2"), "{}", outer[0]);
+}
+
+/// Expectations created on other threads don't use this thread's handler
+#[test]
+fn other_thread() {
+    let log = with_log(|| {
+        let r = std::thread::spawn(|| {
+            let mut mock = MockFoo::new();
+            mock.expect_bar()
+                .times(1)
+                .return_const(());
+        }).join();
+        assert!(r.is_err());
+    });
+    assert!(log.is_empty());
+}
+
+/// A handler still gets failures from mocks that are called on other threads
+#[test]
+fn other_thread_calls() {
+    let log = with_log(|| {
+        let mut mock = MockFoo::new();
+        mock.expect_bar()
+            .times(1)
+            .return_const(());
+        let mock = std::thread::spawn(move || {
+            mock.bar();
+            mock.bar();
+            mock
+        }).join().unwrap();
+        drop(mock);
+    });
+    assert_eq!(log.len(), 1);
+    assert!(log[0].contains("called 2 times"), "{}", log[0]);
+}
+
+#[test]
+fn no_match() {
+    let log = with_log(|| {
+        let mut mock = MockFoo::new();
+        mock.expect_foo()
+            .with(predicate::eq(1))
+            .return_const(0u32);
+        let r = std::panic::catch_unwind(
+            std::panic::AssertUnwindSafe(|| mock.foo(2)));
+        // The mock has nothing to return, so it must still panic
+        assert!(r.is_err());
+    });
+    assert_eq!(log.len(), 1);
+    assert!(log[0].starts_with("MockFoo::foo(2): No matching expectation found"),
+        "{}", log[0]);
+}
+
+#[test]
+fn sequence() {
+    let log = with_log(|| {
+        let mut seq = Sequence::new();
+        let mut mock = MockFoo::new();
+        mock.expect_foo()
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_const(0u32);
+        mock.expect_bar()
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_const(());
+        mock.bar();
+    });
+    assert_eq!(without_locations(&log[0]),
+        "MockFoo::bar(): Method sequence violation.  Expected MockFoo::foo: Expectation(<anything>) (set at HERE) next, but got MockFoo::bar: Expectation(<anything>) (set at HERE, called at HERE)");
+}
+
+#[test]
+fn too_many_calls() {
+    let log = with_log(|| {
+        let mut mock = MockFoo::new();
+        mock.expect_foo()
+            .times(1)
+            .return_const(5u32);
+        assert_eq!(5, mock.foo(1));
+        assert_eq!(5, mock.foo(1));
+    });
+    assert_eq!(log.len(), 1);
+    assert_eq!(without_locations(&log[0]),
+        "MockFoo::foo: Expectation(<anything>) called 2 times which is more than the expected 1 (set at HERE, called at HERE)");
+}
+
+#[test]
+fn verifier_takes_precedence() {
+    let log = with_log(|| {
+        let verifier = Verifier::new();
+        let mut mock = MockFoo::new();
+        mock.expect_bar()
+            .times(1)
+            .return_const(());
+        drop(mock);
+        assert_eq!(1, verifier.verify().unwrap_err().len());
+    });
+    assert!(log.is_empty());
+}
@@ -0,0 +1,91 @@
+// vim: tw=80
+//! Nice and strict mock objects
+#![deny(warnings)]
+
+mod common;
+
+use common::without_locations;
+use mockall::*;
+
+#[automock]
+pub trait Foo {
+    fn foo(&self, x: u32) -> u32;
+    fn name(&self) -> String;
+}
+
+#[test]
+fn nice_returns_default() {
+    let mock = MockFoo::new_nice();
+    assert_eq!(mock.foo(1), 0);
+    assert_eq!(mock.name(), "");
+}
+
+#[test]
+fn nice_uses_default_action() {
+    let mut mock = MockFoo::new_nice();
+    mock.on_call_foo()
+        .return_const(7u32);
+    assert_eq!(mock.foo(1), 7);
+}
+
+#[test]
+fn nice_uses_expectations() {
+    let mut mock = MockFoo::new_nice();
+    mock.expect_foo()
+        .with(predicate::eq(5))
+        .times(1)
+        .return_const(25u32);
+    assert_eq!(mock.foo(5), 25);
+}
+
+/// Nice mocks still check their expectations' call counts
+#[test]
+#[should_panic(expected = "MockFoo::foo: Expectation(var == 5) called 0 time(s) which is fewer than expected 1")]
+fn nice_unsatisfied() {
+    let mut mock = MockFoo::new_nice();
+    mock.expect_foo()
+        .with(predicate::eq(5))
+        .times(1)
+        .return_const(25u32);
+}
+
+#[test]
+#[should_panic(expected = "No matching expectation found")]
+fn default_flavor() {
+    let mock = MockFoo::new();
+    mock.foo(1);
+}
+
+#[test]
+fn strict() {
+    let mut mock = MockFoo::new_strict();
+    mock.expect_foo()
+        .times(1..)
+        .return_const(1u32);
+    mock.expect_name()
+        .times(1)
+        .return_const(String::from("x"));
+    assert_eq!(mock.foo(1), 1);
+    assert_eq!(mock.name(), "x");
+}
+
+#[test]
+fn strict_rejects_default_action() {
+    let verifier = Verifier::new();
+    let mut mock = MockFoo::new_strict();
+    mock.on_call_foo()
+        .return_const(1u32);
+    mock.foo(1);
+    let errors = verifier.verify().unwrap_err();
+    assert_eq!(without_locations(errors.iter().next().unwrap().message()),
+        "MockFoo::foo: Expectation(<anything>) has no call count, which strict mocks require (set at HERE, called at HERE)");
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::foo: Expectation(<anything>) has no call count, which strict mocks require")]
+fn strict_rejects_open_ended() {
+    let mut mock = MockFoo::new_strict();
+    mock.expect_foo()
+        .return_const(1u32);
+    mock.foo(1);
+}
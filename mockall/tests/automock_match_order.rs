@@ -0,0 +1,173 @@
+// vim: tw=80
+//! Expectations may be matched newest first, so later ones override earlier
+//! ones.
+#![deny(warnings)]
+
+use mockall::*;
+use mockall::predicate::*;
+
+#[automock]
+pub trait Foo {
+    fn foo(&self, x: u32) -> u32;
+    fn bar(&self, x: u32) -> u32;
+    fn baz<T: 'static>(&self, t: T) -> u32;
+    fn qux(x: u32) -> u32;
+}
+
+mock! {
+    pub Bean {
+        fn inherent(&self) -> u32;
+    }
+    impl Foo for Bean {
+        fn foo(&self, x: u32) -> u32;
+        fn bar(&self, x: u32) -> u32;
+        fn baz<T: 'static>(&self, t: T) -> u32;
+        fn qux(x: u32) -> u32;
+    }
+}
+
+pub struct Sorter {}
+#[automock]
+impl Sorter {
+    pub fn match_order(&self, _x: u32) -> u32 {
+        unimplemented!()
+    }
+}
+
+fn fixture() -> MockFoo {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .return_const(1u32);
+    mock.expect_bar()
+        .return_const(1u32);
+    mock
+}
+
+#[test]
+fn oldest_first_by_default() {
+    let mut mock = fixture();
+    mock.expect_foo()
+        .with(eq(5))
+        .return_const(2u32);
+    assert_eq!(1, mock.foo(5));
+}
+
+#[test]
+fn newest_first() {
+    let mut mock = fixture();
+    mock.match_order(MatchOrder::NewestFirst);
+    mock.expect_foo()
+        .with(eq(5))
+        .return_const(2u32);
+    mock.expect_bar()
+        .return_const(3u32);
+    assert_eq!(2, mock.foo(5));
+    assert_eq!(1, mock.foo(6));
+    assert_eq!(3, mock.bar(5));
+}
+
+#[test]
+fn per_method() {
+    let mut mock = fixture();
+    mock.match_order_foo(MatchOrder::NewestFirst);
+    mock.expect_foo()
+        .return_const(2u32);
+    mock.expect_bar()
+        .return_const(2u32);
+    assert_eq!(2, mock.foo(5));
+    assert_eq!(1, mock.bar(5));
+}
+
+/// Once the newest expectation is saturated, older ones handle the call.
+#[test]
+fn saturated() {
+    let mut mock = fixture();
+    mock.match_order(MatchOrder::NewestFirst);
+    mock.expect_foo()
+        .times(1)
+        .return_const(2u32);
+    assert_eq!(2, mock.foo(5));
+    assert_eq!(1, mock.foo(5));
+}
+
+/// Default actions are still only used when no expectation matches.
+#[test]
+fn default_actions() {
+    let mut mock = MockFoo::new();
+    mock.match_order(MatchOrder::NewestFirst);
+    mock.on_call_foo()
+        .return_const(1u32);
+    mock.on_call_foo()
+        .return_const(2u32);
+    mock.expect_foo()
+        .with(eq(5))
+        .return_const(3u32);
+    assert_eq!(3, mock.foo(5));
+    assert_eq!(2, mock.foo(6));
+}
+
+#[test]
+fn generic_method() {
+    let mut mock = MockFoo::new();
+    mock.expect_baz::<u32>()
+        .return_const(1u32);
+    mock.match_order(MatchOrder::NewestFirst);
+    mock.expect_baz::<u32>()
+        .return_const(2u32);
+    mock.expect_baz::<i16>()
+        .return_const(3u32);
+    mock.expect_baz::<i16>()
+        .return_const(4u32);
+    assert_eq!(2, mock.baz(0u32));
+    assert_eq!(4, mock.baz(0i16));
+}
+
+#[test]
+fn static_method() {
+    {
+        let ctx = MockFoo::qux_context();
+        ctx.match_order(MatchOrder::NewestFirst);
+        ctx.expect()
+            .return_const(1u32);
+        ctx.expect()
+            .return_const(2u32);
+        assert_eq!(2, MockFoo::qux(5));
+    }
+    // The order resets when the Context drops
+    let ctx = MockFoo::qux_context();
+    ctx.expect()
+        .return_const(1u32);
+    ctx.expect()
+        .return_const(2u32);
+    assert_eq!(1, MockFoo::qux(5));
+}
+
+#[test]
+fn struct_with_trait() {
+    let mut mock = MockBean::new();
+    mock.match_order(MatchOrder::NewestFirst);
+    mock.expect_inherent()
+        .return_const(1u32);
+    mock.expect_inherent()
+        .return_const(2u32);
+    mock.expect_foo()
+        .return_const(1u32);
+    mock.expect_foo()
+        .return_const(2u32);
+    assert_eq!(2, mock.inherent());
+    assert_eq!(2, mock.foo(0));
+}
+
+/// A struct's own match_order method is mocked instead
+#[test]
+fn own_method() {
+    let mut mock = MockSorter::new();
+    mock.match_order_match_order(MatchOrder::NewestFirst);
+    mock.expect_match_order()
+        .return_const(1u32);
+    mock.expect_match_order()
+        .with(eq(5))
+        .return_const(2u32);
+    assert_eq!(2, mock.match_order(5));
+    assert_eq!(1, mock.match_order(6));
+}
@@ -0,0 +1,79 @@
+// vim: tw=80
+//! Expectations and mock objects can be named, to tell them apart in failure
+//! messages.
+#![deny(warnings)]
+
+use mockall::*;
+
+#[automock]
+pub trait Foo {
+    fn foo(&self, x: u32) -> u32;
+    fn bar();
+}
+
+#[test]
+#[should_panic(expected = "MockFoo[replica]::foo: Expectation(<anything>) named \"primary db write\" called 0 time(s) which is fewer than expected 1")]
+fn both() {
+    let mut mock = MockFoo::named("replica");
+    mock.expect_foo()
+        .named("primary db write")
+        .times(1)
+        .return_const(0u32);
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::foo: Expectation(<anything>) named \"primary db write\" called 2 times which is more than the expected 1")]
+fn expectation() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .named("primary db write")
+        .times(1)
+        .return_const(0u32);
+    mock.foo(1);
+    mock.foo(1);
+}
+
+#[test]
+#[should_panic(expected = "Expectation(var == 1) named \"one\" rejected the call")]
+fn expectation_no_match() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .with(predicate::eq(1))
+        .named("one")
+        .return_const(0u32);
+    mock.foo(2);
+}
+
+#[test]
+#[should_panic(expected = "MockFoo[replica]::foo(1): No matching expectation found")]
+fn mock() {
+    let mock = MockFoo::named("replica");
+    mock.foo(1);
+}
+
+#[test]
+#[should_panic(expected = "MockFoo[replica]::foo(1): Method sequence violation")]
+fn mock_sequence() {
+    let mut seq = Sequence::new();
+    let mut primary = MockFoo::named("primary");
+    let mut replica = MockFoo::named("replica");
+    primary.expect_foo()
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(0u32);
+    replica.expect_foo()
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(0u32);
+    replica.foo(1);
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::bar: Expectation(<anything>) named \"static\" called 0 time(s) which is fewer than expected 1")]
+fn static_method() {
+    let ctx = MockFoo::bar_context();
+    ctx.expect()
+        .named("static")
+        .times(1)
+        .return_const(());
+}
@@ -0,0 +1,84 @@
+// vim: tw=80
+//! When no expectation matches a call, the panic message should explain why
+//! each expectation rejected it.
+#![deny(warnings)]
+
+use mockall::*;
+
+#[automock]
+trait Foo {
+    fn foo(&self, x: u32, y: &str) -> u32;
+    fn bar<T: 'static>(&self, t: T);
+    fn baz(x: u32);
+}
+
+#[test]
+#[should_panic(expected = "Expectation(var == 4, true) rejected the call
+    x: var == 4
+       └── var: 7
+Expectation(var == 7, var.starts_with(\"x\")) rejected the call
+    y: var.starts_with(\"x\")
+       └── var: yes")]
+fn predicates() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .with(predicate::eq(4), predicate::always())
+        .return_const(0u32);
+    mock.expect_foo()
+        .with(predicate::eq(7), predicate::str::starts_with("x"))
+        .return_const(0u32);
+    mock.foo(7, "yes");
+}
+
+#[test]
+#[should_panic(expected = "Expectation(<function>) rejected the call: the function returned false")]
+fn function() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .withf(|x, _| *x == 1)
+        .return_const(0u32);
+    mock.foo(7, "yes");
+}
+
+#[test]
+#[should_panic(expected = "No expectations are set for these generic parameters")]
+fn generic_method() {
+    let mut mock = MockFoo::new();
+    mock.expect_bar::<u32>()
+        .return_const(());
+    mock.bar(-1i16);
+}
+
+#[test]
+#[should_panic(expected = "No expectations are set")]
+fn no_expectations() {
+    let mock = MockFoo::new();
+    mock.foo(7, "yes");
+}
+
+#[test]
+#[should_panic(expected = "Expectation(<anything>) matched, but is already saturated
+Expectation(var == 4, true) rejected the call")]
+fn saturated() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(1)
+        .return_const(0u32);
+    mock.expect_foo()
+        .with(predicate::eq(4), predicate::always())
+        .return_const(0u32);
+    mock.foo(7, "yes");
+    mock.foo(7, "yes");
+}
+
+#[test]
+#[should_panic(expected = "Expectation(var == 4) rejected the call
+    x: var == 4
+       └── var: 5")]
+fn static_method() {
+    let ctx = MockFoo::baz_context();
+    ctx.expect()
+        .with(predicate::eq(4))
+        .return_const(());
+    MockFoo::baz(5);
+}
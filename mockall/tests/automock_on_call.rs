@@ -0,0 +1,128 @@
+// vim: tw=80
+//! Default actions set with `on_call_*` are used when no expectation matches,
+//! and have no call count requirement of their own.
+#![deny(warnings)]
+
+use mockall::*;
+use std::time::Duration;
+
+#[automock]
+pub trait Foo {
+    fn foo(&self, x: u32) -> u32;
+    fn bar<T: 'static>(&self, t: T) -> u32;
+    fn baz(x: u32) -> u32;
+}
+
+/// Defaults shared by every test, like a test fixture would set
+fn fixture() -> MockFoo {
+    let mut mock = MockFoo::new();
+    mock.on_call_foo()
+        .with(predicate::eq(42))
+        .return_const(42u32);
+    mock.on_call_foo()
+        .return_const(0u32);
+    mock
+}
+
+#[test]
+fn checkpoint_keeps_defaults() {
+    let mut mock = fixture();
+    mock.expect_foo()
+        .times(1)
+        .return_const(5u32);
+    assert_eq!(mock.foo(1), 5);
+    mock.checkpoint();
+    assert_eq!(mock.foo(1), 0);
+}
+
+#[test]
+fn default_is_used() {
+    let mock = fixture();
+    assert_eq!(mock.foo(1), 0);
+    assert_eq!(mock.foo(42), 42);
+}
+
+#[test]
+fn expectation_takes_precedence() {
+    let mut mock = fixture();
+    mock.expect_foo()
+        .with(predicate::eq(5))
+        .times(1)
+        .return_const(5u32);
+    assert_eq!(mock.foo(1), 0);
+    assert_eq!(mock.foo(5), 5);
+    assert_eq!(mock.foo(2), 0);
+}
+
+/// Once an expectation is saturated, the defaults handle further calls
+#[test]
+fn after_saturation() {
+    let mut mock = fixture();
+    mock.expect_foo()
+        .with(predicate::eq(5))
+        .times(1)
+        .return_const(5u32);
+    mock.expect_foo()
+        .with(predicate::eq(6))
+        .times(1)
+        .return_const(6u32);
+    assert_eq!(mock.foo(5), 5);
+    assert_eq!(mock.foo(6), 6);
+    assert_eq!(mock.foo(5), 0);
+}
+
+#[test]
+fn generic_method() {
+    let mut mock = MockFoo::new();
+    mock.on_call_bar::<u16>()
+        .return_const(1u32);
+    mock.expect_bar::<u16>()
+        .with(predicate::eq(2))
+        .times(1)
+        .return_const(2u32);
+    assert_eq!(mock.bar(1u16), 1);
+    assert_eq!(mock.bar(2u16), 2);
+    mock.checkpoint();
+    assert_eq!(mock.bar(2u16), 1);
+}
+
+#[test]
+#[should_panic(expected = "No matching expectation found")]
+fn no_match() {
+    let mut mock = MockFoo::new();
+    mock.on_call_foo()
+        .with(predicate::eq(2))
+        .return_const(0u32);
+    mock.foo(1);
+}
+
+/// Defaults don't need to be called, and don't count toward verification
+#[test]
+fn not_verified() {
+    let mut mock = fixture();
+    mock.expect_foo()
+        .with(predicate::eq(5))
+        .times(1)
+        .return_const(5u32);
+    assert_eq!(mock.wait_satisfied(Duration::from_millis(0))
+        .unwrap_err()
+        .iter()
+        .count(), 1);
+    mock.foo(5);
+    mock.wait_satisfied(Duration::from_millis(0)).unwrap();
+}
+
+#[test]
+fn static_method() {
+    let ctx = MockFoo::baz_context();
+    ctx.on_call()
+        .returning(|x| x + 1);
+    ctx.expect()
+        .with(predicate::eq(5))
+        .times(1)
+        .return_const(5u32);
+    assert_eq!(MockFoo::baz(1), 2);
+    assert_eq!(MockFoo::baz(5), 5);
+    ctx.checkpoint();
+    assert_eq!(MockFoo::baz(5), 6);
+}
@@ -0,0 +1,72 @@
+// vim: tw=80
+//! Methods marked with #[mockall::real] keep their original bodies
+#![deny(warnings)]
+
+use mockall::*;
+
+pub struct Thing {
+    x: u32
+}
+
+#[automock]
+impl Thing {
+    pub fn new(x: u32) -> Self {
+        Thing{x}
+    }
+    pub fn read(&self) -> u32 {
+        self.x
+    }
+    #[mockall::real]
+    pub fn read_twice(&self) -> u32 {
+        self.read() + self.read()
+    }
+    #[mockall::real]
+    pub fn describe(x: u32) -> String {
+        format!("thing {}", x)
+    }
+}
+
+pub struct Generic<T> {
+    t: T
+}
+
+#[automock]
+impl<T: Clone + 'static> Generic<T> {
+    pub fn get(&self) -> T {
+        self.t.clone()
+    }
+    #[mockall::real]
+    pub fn pair(&self) -> (T, T) {
+        (self.get(), self.get())
+    }
+}
+
+#[test]
+fn generic() {
+    let mut mock = MockGeneric::<u8>::new();
+    mock.expect_get()
+        .return_const(7u8);
+    assert_eq!((7, 7), mock.pair());
+}
+
+#[test]
+fn method() {
+    let mut mock = MockThing::default();
+    mock.expect_read()
+        .times(2)
+        .return_const(21u32);
+    assert_eq!(42, mock.read_twice());
+}
+
+#[test]
+fn static_method() {
+    assert_eq!("thing 5", MockThing::describe(5));
+}
+
+#[test]
+fn unused() {
+    let t = Thing::new(1);
+    assert_eq!(2, t.read_twice());
+    assert_eq!("thing 1", Thing::describe(t.read()));
+    assert_eq!((2, 2), Generic{t: 2}.pair());
+}
@@ -0,0 +1,114 @@
+// vim: tw=80
+//! A mock object can wrap a real object, and forward to it any calls that
+//! match no expectation.
+#![deny(warnings)]
+
+use mockall::*;
+
+#[automock]
+pub trait Foo {
+    fn foo(&self, x: u32) -> u32;
+    fn bar(&mut self, x: &str) -> String;
+    fn baz(&self) -> &u32;
+    fn bean(x: u32) -> u32;
+}
+
+struct Real(u32);
+
+impl Foo for Real {
+    fn foo(&self, x: u32) -> u32 {
+        self.0 + x
+    }
+    fn bar(&mut self, x: &str) -> String {
+        self.0 += 1;
+        format!("{}{}", x, self.0)
+    }
+    fn baz(&self) -> &u32 {
+        &self.0
+    }
+    fn bean(x: u32) -> u32 {
+        x
+    }
+}
+
+#[automock]
+pub trait Generic<T: 'static> {
+    fn generic(&self, t: T) -> T;
+}
+
+struct RealGeneric;
+
+impl Generic<u32> for RealGeneric {
+    fn generic(&self, t: u32) -> u32 {
+        t * 2
+    }
+}
+
+#[test]
+fn call_history() {
+    set_call_history_limit(1000);
+    let mock = MockFoo::wrapping(Real(10));
+    mock.foo(1);
+    assert_eq!(mock.calls_foo()[0].0, 1);
+}
+
+#[test]
+fn expectation_matches() {
+    let mut mock = MockFoo::wrapping(Real(10));
+    mock.expect_foo()
+        .with(predicate::eq(1))
+        .return_const(42u32);
+    assert_eq!(42, mock.foo(1));
+}
+
+#[test]
+fn expectation_does_not_match() {
+    let mut mock = MockFoo::wrapping(Real(10));
+    mock.expect_foo()
+        .with(predicate::eq(1))
+        .return_const(42u32);
+    assert_eq!(12, mock.foo(2));
+}
+
+#[test]
+fn generic_trait() {
+    let mock = MockGeneric::<u32>::wrapping(RealGeneric);
+    assert_eq!(6, mock.generic(3));
+}
+
+#[test]
+fn mutable_receiver() {
+    let mut mock = MockFoo::wrapping(Real(10));
+    assert_eq!("x11", mock.bar("x"));
+    assert_eq!("x12", mock.bar("x"));
+}
+
+#[test]
+fn no_expectations() {
+    let mock = MockFoo::wrapping(Real(10));
+    assert_eq!(15, mock.foo(5));
+}
+
+#[test]
+#[should_panic(expected = "No matching expectation found")]
+fn not_wrapping() {
+    let mock = MockFoo::new();
+    mock.foo(5);
+}
+
+#[test]
+fn return_reference() {
+    let mock = MockFoo::wrapping(Real(10));
+    assert_eq!(10, *mock.baz());
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::foo: Expectation(<anything>) called 2 times which is more than the expected 1")]
+fn saturated_expectation() {
+    let mut mock = MockFoo::wrapping(Real(10));
+    mock.expect_foo()
+        .times(1)
+        .return_const(42u32);
+    mock.foo(1);
+    mock.foo(1);
+}
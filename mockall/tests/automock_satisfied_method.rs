@@ -0,0 +1,55 @@
+// vim: tw=80
+//! A struct with its own satisfied and wait_satisfied methods
+#![deny(warnings)]
+
+use mockall::*;
+use std::time::Duration;
+
+pub struct A {}
+
+#[automock]
+impl A {
+    pub fn satisfied(&self) -> bool {
+        unimplemented!()
+    }
+    pub fn wait_satisfied(&self, _timeout: Duration) -> bool {
+        unimplemented!()
+    }
+}
+
+pub struct B {}
+
+#[automock]
+impl B {
+    pub fn satisfied(&self) -> bool {
+        unimplemented!()
+    }
+    pub fn foo(&self) {
+        unimplemented!()
+    }
+}
+
+#[test]
+fn both() {
+    let mut mock = MockA::new();
+    mock.expect_satisfied()
+        .return_const(true);
+    mock.expect_wait_satisfied()
+        .return_const(false);
+    assert!(mock.satisfied());
+    assert!(!mock.wait_satisfied(Duration::from_secs(1)));
+}
+
+/// The mock's own wait_satisfied is still generated
+#[test]
+fn one() {
+    let mut mock = MockB::new();
+    mock.expect_satisfied()
+        .return_const(true);
+    mock.expect_foo()
+        .times(1)
+        .return_const(());
+    mock.foo();
+    assert!(mock.satisfied());
+    mock.wait_satisfied(Duration::from_millis(0)).unwrap();
+}
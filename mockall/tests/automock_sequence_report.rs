@@ -0,0 +1,164 @@
+// vim: tw=80
+//! A Sequence reports expectations that were never satisfied, and describes
+//! the expectations involved in a sequence violation.
+#![deny(warnings)]
+
+mod common;
+
+use common::without_locations;
+use mockall::*;
+use std::thread;
+
+#[automock]
+pub trait Foo {
+    fn foo(&self, x: u32);
+    fn bar(&self);
+}
+
+/// Set up a mock whose Sequence is dropped before its calls are made
+fn setup() -> MockFoo {
+    let mut seq = Sequence::new();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(());
+    mock.expect_bar()
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(());
+    mock
+}
+
+#[test]
+fn complete() {
+    let mut seq = Sequence::new();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(());
+    mock.expect_bar()
+        .times(1..3)
+        .in_sequence(&mut seq)
+        .return_const(());
+    mock.foo(1);
+    mock.bar();
+    seq.verify_complete().unwrap();
+}
+
+#[test]
+fn incomplete() {
+    let mut seq = Sequence::new();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .with(predicate::eq(1))
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(());
+    mock.expect_bar()
+        .times(1)
+        .in_sequence(&mut seq)
+        .named("finish")
+        .return_const(());
+    mock.foo(1);
+    let errors = seq.verify_complete().unwrap_err();
+    let messages = errors.iter()
+        .map(|e| without_locations(e.message()))
+        .collect::<Vec<_>>();
+    assert_eq!(messages, vec![
+        "Sequence incomplete: MockFoo::bar: Expectation(<anything>) named \"finish\" was not satisfied (set at HERE)"
+    ]);
+    mock.bar();
+}
+
+/// The Sequence can report unsatisfied expectations when dropped, even if
+/// their mock object outlives it.
+#[test]
+fn drop_incomplete() {
+    let verifier = Verifier::new();
+    let mut mock = MockFoo::new();
+    {
+        let mut seq = Sequence::new();
+        seq.verify_on_drop();
+        mock.expect_foo()
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_const(());
+        mock.expect_bar()
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_const(());
+        mock.foo(1);
+    }
+    let errors = verifier.verify().unwrap_err();
+    let messages = errors.iter()
+        .map(|e| without_locations(e.message()))
+        .collect::<Vec<_>>();
+    assert_eq!(messages, vec![
+        "Sequence incomplete: MockFoo::bar: Expectation(<anything>) was not satisfied (set at HERE)"
+    ]);
+    // The expectation itself shouldn't report the same failure again
+    drop(mock);
+    assert_eq!(verifier.verify(), Ok(()));
+}
+
+/// A mock that is used by another thread may never be dropped, but the
+/// Sequence still is.
+#[test]
+#[should_panic(expected = "Sequence incomplete: MockFoo::bar: Expectation")]
+fn drop_incomplete_with_leaked_mock() {
+    let mut seq = Sequence::new();
+    seq.verify_on_drop();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(());
+    mock.expect_bar()
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(());
+    let mock: &'static MockFoo = Box::leak(Box::new(mock));
+    thread::spawn(move || mock.foo(1)).join().unwrap();
+}
+
+/// Without verify_on_drop, a Sequence may be dropped before its calls are
+/// made, for example by a helper function that sets up the mock.
+#[test]
+fn setup_helper() {
+    let mock = setup();
+    mock.foo(1);
+    mock.bar();
+}
+
+/// The sequence is still enforced after the Sequence is dropped
+#[test]
+#[should_panic(expected = "MockFoo::bar(): Method sequence violation")]
+fn setup_helper_violation() {
+    let mock = setup();
+    mock.bar();
+}
+
+#[test]
+fn violation() {
+    let verifier = Verifier::new();
+    let mut seq = Sequence::new();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .with(predicate::eq(1))
+        .times(1)
+        .in_sequence(&mut seq)
+        .named("first")
+        .return_const(());
+    mock.expect_foo()
+        .with(predicate::eq(2))
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(());
+    mock.foo(2);
+    mock.foo(1);
+    let errors = verifier.verify().unwrap_err();
+    assert_eq!(without_locations(errors.iter().next().unwrap().message()),
+        "MockFoo::foo(2): Method sequence violation.  Expected MockFoo::foo: Expectation(var == 1) named \"first\" (set at HERE) next, but got MockFoo::foo: Expectation(var == 2) (set at HERE, called at HERE)");
+}
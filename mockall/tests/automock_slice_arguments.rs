@@ -13,12 +13,9 @@ mod withf {
     use super::*;
 
     #[test]
-    #[cfg_attr(feature = "nightly", should_panic(
-            expected = "MockFoo::foo([1, 2, 3, 4]): No matching expectation found"
-    ))]
-    #[cfg_attr(not(feature = "nightly"), should_panic(
-            expected = "MockFoo::foo(?): No matching expectation found"
-    ))]
+    #[should_panic(
+        expected = "MockFoo::foo([1, 2, 3, 4]): No matching expectation found"
+    )]
     fn fail() {
         let mut mock = MockFoo::new();
         mock.expect_foo()
@@ -0,0 +1,38 @@
+// vim: tw=80
+//! Default return values and argument formatting work without the "nightly"
+//! feature, for non-generic types.
+#![deny(warnings)]
+
+use mockall::*;
+
+#[derive(Debug)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Pair(u32, u32);
+
+#[automock]
+pub trait Foo {
+    fn foo(&self, p: Point, name: &str) -> Pair;
+    fn bar(&self) -> Option<String>;
+}
+
+#[test]
+#[should_panic(expected =
+    "MockFoo::foo(Point { x: 1, y: -2 }, \"abc\"): No matching expectation")]
+fn debug_args() {
+    let mock = MockFoo::new();
+    mock.foo(Point{x: 1, y: -2}, "abc");
+}
+
+#[test]
+fn default_return() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo();
+    mock.expect_bar();
+    assert_eq!(Pair(0, 0), mock.foo(Point{x: 0, y: 0}, ""));
+    assert_eq!(None, mock.bar());
+}
@@ -0,0 +1,104 @@
+// vim: tw=80
+//! Expectations can depend on, and change, the state of a StateMachine
+#![deny(warnings)]
+
+use mockall::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum State {
+    Idle,
+    Connected,
+    Authenticated,
+    Closed
+}
+
+#[automock]
+pub trait Conn {
+    fn connect(&self) -> bool;
+    fn auth(&self, user: &str) -> bool;
+    fn query(&self, q: u32) -> Option<u32>;
+    fn close(&self);
+    fn stat<T: 'static>(&self, t: T) -> u32;
+    fn reset();
+}
+
+fn protocol(machine: &StateMachine<State>, mock: &mut MockConn) {
+    mock.expect_connect()
+        .when_state(machine, State::Idle)
+        .then_state(machine, State::Connected)
+        .return_const(true);
+    mock.expect_auth()
+        .with(predicate::eq("admin"))
+        .when_state(machine, State::Connected)
+        .then_state(machine, State::Authenticated)
+        .return_const(true);
+    mock.expect_query()
+        .when_state(machine, State::Connected)
+        .returning(|_| None);
+    mock.expect_query()
+        .when_state(machine, State::Authenticated)
+        .returning(|q| Some(q + 1));
+    mock.expect_close()
+        .when_state(machine, State::Authenticated)
+        .then_state(machine, State::Closed)
+        .return_const(());
+}
+
+#[test]
+fn by_phase() {
+    let machine = StateMachine::new(State::Idle);
+    let mut mock = MockConn::new();
+    protocol(&machine, &mut mock);
+    assert!(mock.connect());
+    assert_eq!(mock.query(1), None);
+    assert!(mock.auth("admin"));
+    assert_eq!(mock.query(1), Some(2));
+    mock.close();
+    assert_eq!(machine.state(), State::Closed);
+}
+
+#[test]
+fn clone_shares_state() {
+    let machine = StateMachine::new(State::Idle);
+    let machine2 = machine.clone();
+    machine2.set_state(State::Closed);
+    assert_eq!(machine.state(), State::Closed);
+    assert_eq!(format!("{:?}", machine), "StateMachine(Closed)");
+}
+
+#[test]
+fn generic_method() {
+    let machine = StateMachine::new(State::Idle);
+    let mut mock = MockConn::new();
+    mock.expect_stat::<u8>()
+        .when_state(&machine, State::Idle)
+        .then_state(&machine, State::Connected)
+        .return_const(0u32);
+    mock.expect_stat::<u8>()
+        .when_state(&machine, State::Connected)
+        .return_const(1u32);
+    assert_eq!(mock.stat(0u8), 0);
+    assert_eq!(mock.stat(0u8), 1);
+}
+
+#[test]
+#[should_panic(expected = "Expectation(<anything>) matched, but it's only valid in state Authenticated, and the state is Connected")]
+fn wrong_state() {
+    let machine = StateMachine::new(State::Idle);
+    let mut mock = MockConn::new();
+    protocol(&machine, &mut mock);
+    mock.connect();
+    mock.close();
+}
+
+#[test]
+fn static_method() {
+    let ctx = MockConn::reset_context();
+    let machine = StateMachine::new(State::Closed);
+    ctx.expect()
+        .when_state(&machine, State::Closed)
+        .then_state(&machine, State::Idle)
+        .return_const(());
+    MockConn::reset();
+    assert_eq!(machine.state(), State::Idle);
+}
@@ -0,0 +1,124 @@
+// vim: tw=80
+//! A single context object can manage the expectations of every static method
+//! of a mock, or every function of a mocked module.
+#![deny(warnings)]
+
+use mockall::*;
+
+#[automock]
+pub trait Foo {
+    fn new(x: u32) -> Self;
+    fn bar<T: 'static>(t: T) -> u32;
+    fn baz(&self) -> u32;
+}
+
+pub struct Bean {}
+#[automock]
+impl Bean {
+    pub fn create() -> Self { unimplemented!() }
+    pub fn helper(x: u32) -> u32 { x }
+}
+
+mock! {
+    pub Generic<T: 'static> {
+        fn make(t: T) -> u32;
+    }
+}
+
+#[automock]
+pub mod m {
+    pub fn foo(x: u32) -> u32 { x }
+    pub fn bar() -> i16 { 0 }
+}
+
+/// A module that already has its own `context` and `StaticContext`
+#[automock]
+pub mod n {
+    pub type StaticContext = u32;
+    pub fn context() -> StaticContext { 0 }
+}
+
+#[test]
+fn checkpoint() {
+    let ctx = MockFoo::static_context();
+    ctx.expect_new().returning(|_| MockFoo::default());
+    ctx.expect_bar::<i16>().return_const(5u32);
+    MockFoo::new(1);
+    assert_eq!(5, MockFoo::bar(1i16));
+    ctx.checkpoint();
+    // The expectations are gone
+    let e = std::panic::catch_unwind(|| MockFoo::bar(1i16)).unwrap_err();
+    let msg = e.downcast::<String>().unwrap();
+    assert!(msg.contains("No matching expectation found"), "{}", msg);
+}
+
+#[test]
+#[should_panic(expected =
+    "MockFoo::bar: Expectation(<anything>) called 0 time(s) which is fewer than expected 1")]
+fn checkpoint_unsatisfied() {
+    let ctx = MockFoo::static_context();
+    ctx.expect_bar::<u8>()
+        .times(1)
+        .return_const(1u32);
+    ctx.checkpoint();
+}
+
+#[test]
+#[should_panic(expected =
+    "MockBean::helper: Expectation(<anything>) called 0 time(s) which is fewer than expected 1")]
+fn drop_unsatisfied() {
+    let ctx = MockBean::static_context();
+    ctx.expect_create()
+        .returning(MockBean::default);
+    ctx.expect_helper()
+        .times(1)
+        .return_const(2u32);
+    MockBean::create();
+}
+
+#[test]
+fn generic_struct() {
+    let ctx = MockGeneric::<u16>::static_context();
+    ctx.expect_make().returning(|t| u32::from(t) + 1);
+    assert_eq!(6, MockGeneric::<u16>::make(5));
+}
+
+#[test]
+fn module() {
+    let ctx = mock_m::context();
+    ctx.expect_foo().returning(|x| x + 1);
+    ctx.expect_bar().return_const(-1i16);
+    assert_eq!(43, mock_m::foo(42));
+    assert_eq!(-1, mock_m::bar());
+}
+
+/// Don't generate `context` if the module already has one
+#[test]
+fn module_own_context() {
+    let ctx = mock_n::context_context();
+    ctx.expect().return_const(5u32);
+    let x: mock_n::StaticContext = mock_n::context();
+    assert_eq!(5, x);
+}
+
+#[test]
+#[should_panic(expected =
+    "foo: Expectation(<anything>) called 0 time(s) which is fewer than expected 1")]
+fn module_checkpoint() {
+    let ctx = mock_m::context();
+    ctx.expect_foo()
+        .times(1)
+        .return_const(0u32);
+    ctx.checkpoint();
+}
+
+#[test]
+fn on_call() {
+    let ctx = MockBean::static_context();
+    ctx.on_call_helper().returning(|x| x * 2);
+    ctx.expect_helper()
+        .with(predicate::eq(1))
+        .return_const(0u32);
+    assert_eq!(0, MockBean::helper(1));
+    assert_eq!(4, MockBean::helper(2));
+}
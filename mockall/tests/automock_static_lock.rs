@@ -0,0 +1,109 @@
+// vim: tw=80
+//! A static method's Context serializes tests that use the same mock, without
+//! any synchronization of their own.
+#![deny(warnings)]
+
+use mockall::*;
+use std::{
+    sync::{Arc, atomic::{AtomicBool, Ordering}},
+    thread,
+    time::Duration
+};
+
+#[automock]
+pub trait Blocked {
+    fn foo() -> u32;
+    fn bar() -> u32;
+}
+
+#[automock]
+pub trait Reentrant {
+    fn foo() -> u32;
+    fn bar() -> u32;
+}
+
+#[automock]
+pub trait Independent {
+    fn foo() -> u32;
+}
+
+#[automock]
+pub trait OtherThread {
+    fn foo() -> u32;
+}
+
+#[automock]
+pub mod m {
+    pub fn foo() -> u32 { unimplemented!() }
+    pub fn bar() -> u32 { unimplemented!() }
+}
+
+/// A thread creating a Context must wait for other threads' Contexts of the
+/// same mock to drop, even for a different method.
+#[test]
+fn blocks_other_threads() {
+    let ctx = MockBlocked::foo_context();
+    ctx.expect().return_const(1u32);
+    let locked = Arc::new(AtomicBool::new(false));
+    let locked2 = locked.clone();
+    let t = thread::spawn(move || {
+        let ctx = MockBlocked::bar_context();
+        locked2.store(true, Ordering::SeqCst);
+        ctx.expect().return_const(2u32);
+        MockBlocked::bar()
+    });
+    thread::sleep(Duration::from_millis(50));
+    assert!(!locked.load(Ordering::SeqCst));
+    assert_eq!(1, MockBlocked::foo());
+    drop(ctx);
+    assert_eq!(2, t.join().unwrap());
+    assert!(locked.load(Ordering::SeqCst));
+}
+
+/// One thread may hold several Contexts for the same mock at once
+#[test]
+fn reentrant() {
+    let foo_ctx = MockReentrant::foo_context();
+    let bar_ctx = MockReentrant::bar_context();
+    foo_ctx.expect().return_const(1u32);
+    bar_ctx.expect().return_const(2u32);
+    assert_eq!(1, MockReentrant::foo());
+    assert_eq!(2, MockReentrant::bar());
+    let foo_ctx2 = MockReentrant::foo_context();
+    drop(foo_ctx2);
+}
+
+/// Functions of the same mocked module share a lock, too
+#[test]
+fn module() {
+    let foo_ctx = mock_m::foo_context();
+    let bar_ctx = mock_m::bar_context();
+    foo_ctx.expect().return_const(1u32);
+    bar_ctx.expect().return_const(2u32);
+    assert_eq!(1, mock_m::foo());
+    assert_eq!(2, mock_m::bar());
+}
+
+/// Different mocks don't share a lock
+#[test]
+fn independent() {
+    let _ctx = MockBlocked::foo_context();
+    thread::spawn(|| {
+        let ctx = MockIndependent::foo_context();
+        ctx.expect().return_const(3u32);
+        MockIndependent::foo()
+    }).join().unwrap();
+}
+
+/// A Context may set expectations from another thread than the one that
+/// created it, like a task that moves between an executor's threads.
+#[test]
+fn other_thread() {
+    let ctx = MockOtherThread::foo_context();
+    let ctx = thread::spawn(move || {
+        ctx.expect().return_const(4u32);
+        ctx
+    }).join().unwrap();
+    assert_eq!(4, MockOtherThread::foo());
+    drop(ctx);
+}
@@ -0,0 +1,61 @@
+// vim: tw=80
+//! A panic during one test must not break later tests that use the same static
+//! method, even if it poisoned the method's expectations.
+#![deny(warnings)]
+
+use mockall::*;
+use std::{panic, thread};
+
+#[automock]
+pub trait Foo {
+    fn bar(x: u32) -> u32;
+    fn baz(x: u32) -> u32;
+    fn bean(x: u32) -> u32;
+}
+
+/// A `returning` closure that panics poisons the expectations' mutex
+#[test]
+fn returning_panics() {
+    thread::spawn(|| {
+        let ctx = MockFoo::bar_context();
+        ctx.expect()
+            .returning(|_| panic!("Oops"));
+        MockFoo::bar(1)
+    }).join().unwrap_err();
+
+    let ctx = MockFoo::bar_context();
+    ctx.expect()
+        .return_const(5u32);
+    assert_eq!(5, MockFoo::bar(1));
+}
+
+/// The panic may also be caught by the caller, with the Context still alive
+#[test]
+fn caught_panic() {
+    let ctx = MockFoo::baz_context();
+    ctx.expect()
+        .times(1)
+        .returning(|_| panic!("Oops"));
+    panic::catch_unwind(|| MockFoo::baz(1)).unwrap_err();
+    ctx.checkpoint();
+    ctx.expect()
+        .return_const(5u32);
+    assert_eq!(5, MockFoo::baz(1));
+}
+
+/// Expectations left behind by a failing test are discarded
+#[test]
+fn stale_expectations() {
+    thread::spawn(|| {
+        let ctx = MockFoo::bean_context();
+        ctx.expect()
+            .times(1)
+            .return_const(0u32);
+        panic!("Test failed");
+    }).join().unwrap_err();
+
+    let ctx = MockFoo::bean_context();
+    ctx.expect()
+        .return_const(5u32);
+    assert_eq!(5, MockFoo::bean(1));
+}
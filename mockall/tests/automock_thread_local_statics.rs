@@ -0,0 +1,145 @@
+// vim: tw=80
+//! With `statics = thread_local`, tests on different threads can set
+//! expectations on the same static method independently.
+#![deny(warnings)]
+
+use mockall::*;
+use std::{
+    sync::{Arc, Barrier},
+    thread
+};
+
+#[automock(statics = thread_local)]
+pub trait Foo {
+    fn foo(x: u32) -> u32;
+    fn bar<T: 'static>(t: T) -> u32;
+}
+
+#[automock(statics = thread_local; type Output = u32;)]
+pub trait Bar {
+    type Output;
+    fn baz() -> Self::Output;
+}
+
+pub struct Baz {}
+#[automock(statics = thread_local)]
+impl Baz {
+    pub fn baz() -> u32 { unimplemented!() }
+}
+
+#[automock(statics = thread_local)]
+pub mod m {
+    pub fn foo() -> u32 { unimplemented!() }
+}
+
+fn panic_message(e: Box<dyn std::any::Any + Send>) -> String {
+    e.downcast::<String>().map(|s| *s).unwrap()
+}
+
+/// Run `f` on two threads at once, passing it a different number on each.
+/// Each thread holds its Context until both have set their expectations.
+fn in_parallel<F>(f: F)
+    where F: Fn(u32, &Barrier) + Send + Sync + 'static
+{
+    let f = Arc::new(f);
+    let barrier = Arc::new(Barrier::new(2));
+    let threads = (1..=2).map(|i| {
+        let f = f.clone();
+        let barrier = barrier.clone();
+        thread::spawn(move || f(i, &barrier))
+    }).collect::<Vec<_>>();
+    for t in threads {
+        t.join().unwrap();
+    }
+}
+
+#[test]
+fn generic_method() {
+    in_parallel(|i, barrier| {
+        let ctx = MockFoo::bar_context();
+        ctx.expect::<i16>().return_const(i);
+        barrier.wait();
+        assert_eq!(i, MockFoo::bar(0i16));
+    });
+}
+
+#[test]
+fn module() {
+    in_parallel(|i, barrier| {
+        let ctx = mock_m::foo_context();
+        ctx.expect().return_const(i);
+        barrier.wait();
+        assert_eq!(i, mock_m::foo());
+    });
+}
+
+/// Calls on other threads don't match this thread's expectations
+#[test]
+fn other_thread_calls() {
+    let ctx = MockFoo::foo_context();
+    ctx.expect().return_const(1u32);
+    let e = thread::spawn(|| MockFoo::foo(5)).join().unwrap_err();
+    let msg = panic_message(e);
+    assert!(msg.starts_with("MockFoo::foo(5): No matching expectation found"),
+        "{}", msg);
+    assert_eq!(1, MockFoo::foo(5));
+}
+
+#[test]
+fn static_method() {
+    in_parallel(|i, barrier| {
+        let ctx = MockFoo::foo_context();
+        ctx.expect().returning(move |x| x + i);
+        barrier.wait();
+        assert_eq!(10 + i, MockFoo::foo(10));
+    });
+}
+
+#[test]
+fn struct_() {
+    in_parallel(|i, barrier| {
+        let ctx = MockBaz::baz_context();
+        ctx.expect().return_const(i);
+        barrier.wait();
+        assert_eq!(i, MockBaz::baz());
+    });
+}
+
+#[test]
+fn with_type_attr() {
+    in_parallel(|i, barrier| {
+        let ctx = MockBar::baz_context();
+        ctx.expect().return_const(i);
+        barrier.wait();
+        assert_eq!(i, MockBar::baz());
+    });
+}
+
+/// An ExpectationGuard's methods can be chained
+#[test]
+fn chained() {
+    let ctx = MockFoo::foo_context();
+    ctx.expect()
+        .with(predicate::eq(4))
+        .times(1)
+        .returning(|x| x + 1);
+    assert_eq!(5, MockFoo::foo(4));
+    ctx.checkpoint();
+}
+
+/// A thread's expectations are dropped when it exits
+#[test]
+fn dropped_on_thread_exit() {
+    let captured = Arc::new(());
+    let c = captured.clone();
+    thread::spawn(move || {
+        let ctx = MockFoo::foo_context();
+        ctx.expect().returning(move |_| {
+            let _ = &c;
+            0
+        });
+        // Leak the Context, so it doesn't clear the expectations.
+        std::mem::forget(ctx);
+    }).join().unwrap();
+    assert_eq!(1, Arc::strong_count(&captured));
+}
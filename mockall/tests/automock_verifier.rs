@@ -0,0 +1,133 @@
+// vim: tw=80
+//! A Verifier collects mock failures instead of panicking at the first one
+#![deny(warnings)]
+
+mod common;
+
+use common::without_locations;
+use mockall::*;
+use std::thread;
+
+#[automock]
+trait Foo {
+    fn foo(&self, x: u32) -> u32;
+    fn bar(&self);
+}
+
+#[test]
+fn collects_all_failures() {
+    let verifier = Verifier::new();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(1)
+        .return_const(0u32);
+    mock.expect_bar()
+        .times(1)
+        .return_const(());
+    assert_eq!(0, mock.foo(1));
+    assert_eq!(0, mock.foo(2));
+    mock.checkpoint();
+
+    let errors = verifier.verify().unwrap_err();
+    let messages = errors.iter()
+        .map(|e| without_locations(e.message()))
+        .collect::<Vec<_>>();
+    assert_eq!(messages, vec![
+        "MockFoo::foo: Expectation(<anything>) called 2 times which is more than the expected 1 (set at HERE, called at HERE)",
+        "MockFoo::bar: Expectation(<anything>) called 0 time(s) which is fewer than expected 1 (set at HERE)"
+    ]);
+}
+
+#[test]
+#[should_panic(expected = "2 mock failure(s)
+MockFoo::foo: Expectation(<anything>) called 2 times which is more than the expected 1")]
+fn drop_with_unreported_failures() {
+    let _verifier = Verifier::new();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(1)
+        .return_const(0u32);
+    mock.expect_bar()
+        .times(1)
+        .return_const(());
+    mock.foo(1);
+    mock.foo(1);
+}
+
+#[test]
+fn no_failures() {
+    let verifier = Verifier::new();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(1)
+        .return_const(0u32);
+    mock.foo(1);
+    drop(mock);
+    assert_eq!(Ok(()), verifier.verify());
+}
+
+#[test]
+fn sequence() {
+    let verifier = Verifier::new();
+    let mut seq = Sequence::new();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(0u32);
+    mock.expect_bar()
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(());
+    mock.bar();
+    mock.foo(1);
+
+    let errors = verifier.verify().unwrap_err();
+    assert_eq!(without_locations(errors.iter().next().unwrap().message()),
+        "MockFoo::bar(): Method sequence violation.  Expected MockFoo::foo: Expectation(<anything>) (set at HERE) next, but got MockFoo::bar: Expectation(<anything>) (set at HERE, called at HERE)");
+}
+
+#[test]
+fn unmatched_call_in_worker_thread() {
+    let verifier = Verifier::new();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .with(predicate::eq(1))
+        .return_const(0u32);
+    let r = thread::spawn(move || mock.foo(2)).join();
+    assert!(r.is_err());
+
+    let errors = verifier.verify().unwrap_err();
+    assert_eq!(errors.len(), 1);
+    let message = errors.iter().next().unwrap().message();
+    assert!(message.starts_with("MockFoo::foo(2): No matching expectation found"),
+        "{}", message);
+}
+
+#[test]
+fn verify_forgets_reported_failures() {
+    let verifier = Verifier::new();
+    let mut mock = MockFoo::new();
+    mock.expect_bar()
+        .times(1)
+        .return_const(());
+    mock.checkpoint();
+    assert_eq!(1, verifier.verify().unwrap_err().len());
+    assert!(verifier.verify().is_ok());
+}
+
+#[test]
+fn worker_thread() {
+    let verifier = Verifier::new();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(1)
+        .return_const(0u32);
+    thread::spawn(move || {
+        mock.foo(1);
+        mock.foo(1);
+    }).join().unwrap();
+
+    let errors = verifier.verify().unwrap_err();
+    assert_eq!(errors.len(), 1);
+}
@@ -0,0 +1,117 @@
+// vim: tw=80
+//! A test can wait until a mock's expectations have been satisfied by other
+//! threads.
+#![deny(warnings)]
+
+mod common;
+
+use common::without_locations;
+use futures::executor::block_on;
+use mockall::*;
+use std::{
+    sync::Arc,
+    thread,
+    time::Duration
+};
+
+#[automock]
+pub trait Foo {
+    fn foo(&self, x: u32) -> u32;
+    fn bar<T: 'static>(&self, t: T);
+    fn baz();
+}
+
+const LONG: Duration = Duration::from_secs(60);
+
+#[test]
+fn already_satisfied() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .return_const(0u32);
+    mock.wait_satisfied(Duration::from_millis(0)).unwrap();
+    block_on(mock.satisfied());
+}
+
+#[test]
+fn async_satisfied() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(2)
+        .return_const(0u32);
+    let mock = Arc::new(mock);
+    let mock2 = mock.clone();
+    let t = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        mock2.foo(1);
+        thread::sleep(Duration::from_millis(10));
+        mock2.foo(2);
+    });
+    block_on(mock.satisfied());
+    t.join().unwrap();
+}
+
+#[test]
+fn generic_method() {
+    let mut mock = MockFoo::new();
+    mock.expect_bar::<u32>()
+        .times(1)
+        .return_const(());
+    let mock = Arc::new(mock);
+    let mock2 = mock.clone();
+    let t = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        mock2.bar(5u32);
+    });
+    mock.wait_satisfied(LONG).unwrap();
+    t.join().unwrap();
+}
+
+/// Static methods' expectations don't belong to any mock object
+#[test]
+fn static_method() {
+    let ctx = MockFoo::baz_context();
+    ctx.expect()
+        .times(1)
+        .return_const(());
+    let mock = MockFoo::new();
+    mock.wait_satisfied(Duration::from_millis(0)).unwrap();
+    MockFoo::baz();
+}
+
+#[test]
+fn timeout() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(1)
+        .return_const(0u32);
+    mock.expect_bar::<i16>()
+        .times(1)
+        .return_const(());
+    mock.bar(1i16);
+    let errors = mock.wait_satisfied(Duration::from_millis(10)).unwrap_err();
+    let messages = errors.iter()
+        .map(|e| without_locations(e.message()))
+        .collect::<Vec<_>>();
+    assert_eq!(messages, vec![
+        "MockFoo::foo: Expectation(<anything>) called 0 time(s) which is fewer than expected 1 (set at HERE)"
+    ]);
+    mock.foo(1);
+}
+
+#[test]
+fn wait_satisfied() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(2)
+        .return_const(0u32);
+    let mock = Arc::new(mock);
+    let mock2 = mock.clone();
+    let t = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        mock2.foo(1);
+        thread::sleep(Duration::from_millis(10));
+        mock2.foo(2);
+    });
+    mock.wait_satisfied(LONG).unwrap();
+    t.join().unwrap();
+}
@@ -0,0 +1,20 @@
+// vim: tw=80
+//! Helpers shared by several integration tests
+
+/// Replace every source location in a failure message, like
+/// `mockall/tests/foo.rs:12:10`, with `HERE`, so expected messages don't
+/// depend on the layout of the test file.
+pub fn without_locations(msg: &str) -> String {
+    let mut out = String::new();
+    let mut rest = msg;
+    while let Some(i) = rest.find(".rs:") {
+        let start = rest[..i].rfind(|c| c == ' ' || c == '(')
+            .map_or(0, |j| j + 1);
+        out.push_str(&rest[..start]);
+        out.push_str("HERE");
+        rest = rest[i + ".rs".len()..]
+            .trim_start_matches(|c: char| c == ':' || c.is_ascii_digit());
+    }
+    out.push_str(rest);
+    out
+}
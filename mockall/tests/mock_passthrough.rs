@@ -0,0 +1,73 @@
+// vim: tw=80
+//! A mock struct that implements several traits can wrap a real object that
+//! implements all of them.
+#![deny(warnings)]
+
+use mockall::*;
+
+pub trait Named {
+    fn name(&self) -> String;
+}
+
+struct Counter(u32);
+
+impl Iterator for Counter {
+    type Item = u32;
+    fn next(&mut self) -> Option<u32> {
+        self.0 += 1;
+        Some(self.0)
+    }
+}
+
+impl Named for Counter {
+    fn name(&self) -> String {
+        String::from("counter")
+    }
+}
+
+impl Clone for Counter {
+    fn clone(&self) -> Self {
+        Counter(self.0)
+    }
+}
+
+mock! {
+    pub Counter {}
+    impl Clone for Counter {
+        fn clone(&self) -> Self;
+    }
+    impl Iterator for Counter {
+        type Item = u32;
+        fn next(&mut self) -> Option<u32>;
+    }
+    impl Named for Counter {
+        fn name(&self) -> String;
+    }
+}
+
+#[test]
+fn associated_types() {
+    let mut mock = MockCounter::wrapping(Counter(0));
+    mock.expect_next()
+        .times(1)
+        .return_const(None);
+    assert_eq!(None, mock.next());
+    mock.checkpoint();
+    assert_eq!(Some(1), mock.next());
+    assert_eq!(Some(2), mock.next());
+}
+
+#[test]
+fn multiple_traits() {
+    let mut mock = MockCounter::wrapping(Counter(0));
+    assert_eq!("counter", mock.name());
+    assert_eq!(Some(1), mock.next());
+}
+
+/// Methods that mention the mock type itself can't be forwarded.
+#[test]
+#[should_panic(expected = "MockCounter::clone(): No matching expectation found")]
+fn self_type() {
+    let mock = MockCounter::wrapping(Counter(0));
+    let _ = mock.clone();
+}
@@ -46,12 +46,7 @@ fn ctx_checkpoint() {
 
 // Expectations should be cleared when a context object drops
 #[test]
-#[cfg_attr(feature = "nightly", should_panic(
-        expected = "MockFoo::bar3(42): No matching expectation found"
-))]
-#[cfg_attr(not(feature = "nightly"), should_panic(
-        expected = "MockFoo::bar3(?): No matching expectation found"
-))]
+#[should_panic(expected = "MockFoo::bar3(42): No matching expectation found")]
 fn ctx_hygiene() {
     {
         let ctx0 = MockFoo::bar3_context();
@@ -71,8 +66,6 @@ fn return_const() {
     assert_eq!(42, MockFoo::bar(41));
 }
 
-#[cfg_attr(not(feature = "nightly"), ignore)]
-#[cfg_attr(not(feature = "nightly"), allow(unused_must_use))]
 #[test]
 fn return_default() {
     let _m = BAR_MTX.lock().unwrap();
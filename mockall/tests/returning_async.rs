@@ -0,0 +1,102 @@
+// vim: tw=80
+//! An expectation for an async method can compute its return value
+//! asynchronously
+#![deny(warnings)]
+
+use async_trait::async_trait;
+use futures::{
+    channel::oneshot,
+    executor::block_on,
+    future::{self, Future}
+};
+use mockall::*;
+use std::{
+    pin::Pin,
+    sync::Mutex,
+    thread
+};
+
+mock! {
+    pub Foo {
+        async fn foo(&self, x: u32) -> u32;
+        async fn bar() -> u32;
+        async fn baz<T: Send + 'static>(&self, t: T) -> T;
+        fn bean(&self) -> impl Future<Output=u32> + Send;
+        fn pinned(&self, x: u32) -> Pin<Box<dyn Future<Output=u32>>>;
+    }
+}
+
+#[automock]
+#[async_trait]
+pub trait Bar {
+    async fn bar(&self, x: u32) -> u32;
+}
+
+#[test]
+fn async_fn() {
+    let (tx, rx) = oneshot::channel::<u32>();
+    let rx = Mutex::new(Some(rx));
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .returning_async(move |x| {
+            let rx = rx.lock().unwrap().take().unwrap();
+            async move { rx.await.unwrap() + x }
+        });
+    let t = thread::spawn(move || tx.send(42).unwrap());
+    assert_eq!(block_on(mock.foo(1)), 43);
+    t.join().unwrap();
+}
+
+#[test]
+fn async_trait() {
+    let mut mock = MockBar::new();
+    mock.expect_bar()
+        .returning_async(|x| async move { x + 1 });
+    assert_eq!(block_on(mock.bar(1)), 2);
+}
+
+#[test]
+fn generic_method() {
+    let mut mock = MockFoo::new();
+    mock.expect_baz::<u32>()
+        .returning_async(|x| future::ready(x + 1));
+    assert_eq!(block_on(mock.baz(1u32)), 2);
+}
+
+#[test]
+fn impl_future() {
+    let mut mock = MockFoo::new();
+    mock.expect_bean()
+        .returning_async(|| async { 42 });
+    assert_eq!(block_on(mock.bean()), 42);
+}
+
+#[test]
+fn pinned_future() {
+    let mut mock = MockFoo::new();
+    mock.expect_pinned()
+        .returning_async(|x| async move { x * 2 });
+    assert_eq!(block_on(mock.pinned(21)), 42);
+}
+
+/// returning_async can be combined with the other return methods
+#[test]
+fn return_const() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .with(predicate::eq(1))
+        .return_const(1u32);
+    mock.expect_foo()
+        .with(predicate::eq(2))
+        .returning_async(|x| async move { x * 2 });
+    assert_eq!(block_on(mock.foo(1)), 1);
+    assert_eq!(block_on(mock.foo(2)), 4);
+}
+
+#[test]
+fn static_method() {
+    let ctx = MockFoo::bar_context();
+    ctx.expect()
+        .returning_async(|| async { 42 });
+    assert_eq!(block_on(MockFoo::bar()), 42);
+}
@@ -0,0 +1,89 @@
+// vim: tw=80
+//! An expectation for an async method can return a future that the test
+//! completes by hand.
+#![deny(warnings)]
+
+use futures::{
+    FutureExt,
+    executor::block_on,
+    future::{self, Either, Future}
+};
+use mockall::*;
+use std::thread;
+
+mock! {
+    pub Foo {
+        async fn fetch(&self, key: u32) -> Result<u32, String>;
+        async fn bar() -> u32;
+        fn bean(&self) -> impl Future<Output=u32> + Send;
+    }
+}
+
+#[test]
+fn cancelled() {
+    let mut mock = MockFoo::new();
+    let handle = mock.expect_fetch()
+        .returning_handle();
+    let r = block_on(async {
+        match future::select(mock.fetch(1).boxed(), future::ready(0)).await {
+            Either::Left(_) => unreachable!(),
+            Either::Right((x, _)) => x
+        }
+    });
+    assert_eq!(r, 0);
+    assert!(handle.is_polled());
+    assert!(handle.is_cancelled());
+}
+
+#[test]
+fn complete() {
+    let mut mock = MockFoo::new();
+    let handle = mock.expect_fetch()
+        .returning_handle();
+    let mut fut = mock.fetch(1).boxed();
+    assert!(!handle.is_polled());
+    assert!((&mut fut).now_or_never().is_none());
+    assert!(handle.is_polled());
+    handle.complete(Ok(42));
+    assert_eq!(fut.now_or_never(), Some(Ok(42)));
+    assert!(!handle.is_cancelled());
+}
+
+#[test]
+fn complete_from_another_thread() {
+    let mut mock = MockFoo::new();
+    let handle = mock.expect_fetch()
+        .returning_handle();
+    let t = thread::spawn(move || handle.complete(Ok(42)));
+    assert_eq!(block_on(mock.fetch(1)), Ok(42));
+    t.join().unwrap();
+}
+
+#[test]
+fn fail() {
+    let mut mock = MockFoo::new();
+    mock.expect_fetch()
+        .returning_handle()
+        .fail(String::from("timed out"));
+    assert_eq!(block_on(mock.fetch(1)), Err(String::from("timed out")));
+}
+
+#[test]
+fn impl_future() {
+    let mut mock = MockFoo::new();
+    let handle = mock.expect_bean()
+        .returning_handle();
+    let mut fut = mock.bean();
+    assert!((&mut fut).now_or_never().is_none());
+    handle.complete(42);
+    assert_eq!(fut.now_or_never(), Some(42));
+}
+
+#[test]
+fn static_method() {
+    let ctx = MockFoo::bar_context();
+    let handle = ctx.expect()
+        .returning_handle();
+    handle.complete(42);
+    assert_eq!(block_on(MockFoo::bar()), 42);
+}
@@ -0,0 +1,235 @@
+// vim: tw=80
+//! In a rendezvous Sequence, a call that arrives too early waits for its turn
+//! instead of failing.
+#![deny(warnings)]
+
+use futures::executor::block_on;
+mod common;
+
+use common::without_locations;
+use mockall::*;
+use std::{
+    sync::Arc,
+    thread,
+    time::{Duration, Instant}
+};
+
+mock! {
+    pub Foo {
+        fn foo(&self, x: u32);
+        async fn bar(&self, x: u32) -> u32;
+        async fn baz<T: Send + 'static>(&self, t: T) -> T;
+        async fn bean() -> u32;
+        fn qux(x: u32);
+        #[mockall::concretize]
+        fn quux<T: AsRef<str>>(t: T);
+    }
+}
+
+const LONG: Duration = Duration::from_secs(60);
+
+fn two_foos(seq: &mut Sequence<Rendezvous>, mock: &mut MockFoo) {
+    mock.expect_foo()
+        .with(predicate::eq(1))
+        .times(1)
+        .in_sequence(seq)
+        .return_const(());
+    mock.expect_foo()
+        .with(predicate::eq(2))
+        .times(1)
+        .in_sequence(seq)
+        .return_const(());
+}
+
+fn two_bars(seq: &mut Sequence<Rendezvous>, mock: &mut MockFoo) {
+    mock.expect_bar()
+        .with(predicate::eq(1))
+        .times(1)
+        .in_sequence(seq)
+        .return_const(1u32);
+    mock.expect_bar()
+        .with(predicate::eq(2))
+        .times(1)
+        .in_sequence(seq)
+        .return_const(2u32);
+}
+
+#[test]
+fn early_call_waits() {
+    let mut seq = Sequence::rendezvous(LONG);
+    let mut mock = MockFoo::new();
+    two_foos(&mut seq, &mut mock);
+    let mock = Arc::new(mock);
+    let mock2 = mock.clone();
+    let t = thread::spawn(move || mock2.foo(2));
+    thread::sleep(Duration::from_millis(10));
+    mock.foo(1);
+    t.join().unwrap();
+}
+
+#[test]
+fn in_order() {
+    let mut seq = Sequence::rendezvous(LONG);
+    let mut mock = MockFoo::new();
+    two_foos(&mut seq, &mut mock);
+    mock.foo(1);
+    mock.foo(2);
+}
+
+/// A static method's early call doesn't lock out the call it's waiting for
+#[test]
+fn static_method() {
+    let ctx = MockFoo::qux_context();
+    let mut seq = Sequence::rendezvous(LONG);
+    ctx.expect()
+        .with(predicate::eq(1))
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(());
+    ctx.expect()
+        .with(predicate::eq(2))
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(());
+    let start = Instant::now();
+    let t = thread::spawn(|| MockFoo::qux(2));
+    thread::sleep(Duration::from_millis(10));
+    MockFoo::qux(1);
+    t.join().unwrap();
+    assert!(start.elapsed() < LONG);
+}
+
+/// A static method that can't wait for its turn may still join a plain
+/// Sequence.  Joining a rendezvous one won't compile.
+#[test]
+fn static_method_concretize() {
+    let ctx = MockFoo::quux_context();
+    let mut seq = Sequence::new();
+    ctx.expect()
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(());
+    MockFoo::quux("x");
+    seq.verify_complete().unwrap();
+}
+
+/// A too-late static call fails without waiting, even though it waits for its
+/// turn before calling the expectation.
+#[test]
+fn static_method_too_late() {
+    let verifier = Verifier::new();
+    let ctx = MockFoo::qux_context();
+    let mut seq = Sequence::rendezvous(LONG);
+    ctx.expect()
+        .with(predicate::eq(1))
+        .times(1..5)
+        .in_sequence(&mut seq)
+        .return_const(());
+    ctx.expect()
+        .with(predicate::eq(2))
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(());
+    let start = Instant::now();
+    MockFoo::qux(1);
+    MockFoo::qux(2);
+    MockFoo::qux(1);
+    assert!(start.elapsed() < LONG);
+    assert_eq!(verifier.verify().unwrap_err().len(), 1);
+}
+
+#[test]
+fn timeout() {
+    let verifier = Verifier::new();
+    let mut seq = Sequence::rendezvous(Duration::from_millis(10));
+    let mut mock = MockFoo::new();
+    two_foos(&mut seq, &mut mock);
+    mock.foo(2);
+    mock.foo(1);
+    let errors = verifier.verify().unwrap_err();
+    assert_eq!(without_locations(errors.iter().next().unwrap().message()),
+        "MockFoo::foo(2): Method sequence violation.  Expected MockFoo::foo: Expectation(var == 1) (set at HERE) next, but got MockFoo::foo: Expectation(var == 2), even after waiting 10ms (set at HERE, called at HERE)");
+}
+
+/// A call whose turn has already passed fails without waiting
+#[test]
+#[should_panic(expected = "MockFoo::foo(1): Method sequence violation.  Expected MockFoo::bar: Expectation(<anything>)")]
+fn too_late() {
+    let mut seq = Sequence::rendezvous(LONG);
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(1..5)
+        .in_sequence(&mut seq)
+        .return_const(());
+    mock.expect_bar()
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(0u32);
+    mock.foo(1);
+    block_on(mock.bar(0));
+    mock.foo(1);
+}
+
+/// Async methods wait without blocking the executor, so both calls can make
+/// progress on a single thread.
+#[test]
+fn async_early_call_waits() {
+    let mut seq = Sequence::rendezvous(LONG);
+    let mut mock = MockFoo::new();
+    two_bars(&mut seq, &mut mock);
+    let (r2, r1) = block_on(async {
+        futures::join!(mock.bar(2), mock.bar(1))
+    });
+    assert_eq!((r1, r2), (1, 2));
+}
+
+#[test]
+fn async_generic_method() {
+    let mut seq = Sequence::rendezvous(LONG);
+    let mut mock = MockFoo::new();
+    mock.expect_baz::<u32>()
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|x| x);
+    mock.expect_baz::<i16>()
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|x| x);
+    let (r2, r1) = block_on(async {
+        futures::join!(mock.baz(2i16), mock.baz(1u32))
+    });
+    assert_eq!((r1, r2), (1, 2));
+}
+
+#[test]
+fn async_static_method() {
+    let ctx = MockFoo::bean_context();
+    let mut seq = Sequence::rendezvous(LONG);
+    let mut mock = MockFoo::new();
+    mock.expect_bar()
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(1u32);
+    ctx.expect()
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(2u32);
+    let (r2, r1) = block_on(async {
+        futures::join!(MockFoo::bean(), mock.bar(1))
+    });
+    assert_eq!((r1, r2), (1, 2));
+}
+
+#[test]
+fn async_timeout() {
+    let verifier = Verifier::new();
+    let mut seq = Sequence::rendezvous(Duration::from_millis(10));
+    let mut mock = MockFoo::new();
+    two_bars(&mut seq, &mut mock);
+    assert_eq!(block_on(mock.bar(2)), 2);
+    block_on(mock.bar(1));
+    let errors = verifier.verify().unwrap_err();
+    assert!(without_locations(errors.iter().next().unwrap().message())
+        .starts_with("MockFoo::bar(2): Method sequence violation.  Expected MockFoo::bar: Expectation(var == 1) (set at HERE) next, but got MockFoo::bar: Expectation(var == 2), even after waiting 10ms"),
+        "{}", errors);
+}
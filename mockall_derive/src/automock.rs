@@ -8,6 +8,8 @@ use syn::parse::{Parse, ParseStream};
 #[allow(clippy::large_enum_variant)]
 enum Attr {
     Mod(ItemMod),
+    /// `statics = thread_local` or `statics = global`.  True for thread_local.
+    Statics(bool),
     Type(TraitItemType),
 }
 
@@ -18,6 +20,26 @@ impl Parse for Attr {
             input.parse().map(Attr::Mod)
         } else if lookahead.peek(Token![type]) {
             input.parse().map(Attr::Type)
+        } else if lookahead.peek(Ident) {
+            let key: Ident = input.parse()?;
+            if key != "statics" {
+                return Err(parse::Error::new(key.span(),
+                    "unknown automock attribute"));
+            }
+            input.parse::<Token![=]>()?;
+            let value: Ident = input.parse()?;
+            let thread_local = if value == "thread_local" {
+                true
+            } else if value == "global" {
+                false
+            } else {
+                return Err(parse::Error::new(value.span(),
+                    "statics must be either thread_local or global"));
+            };
+            if input.peek(Token![;]) {
+                input.parse::<Token![;]>()?;
+            }
+            Ok(Attr::Statics(thread_local))
         } else {
             Err(lookahead.error())
         }
@@ -28,7 +50,9 @@ impl Parse for Attr {
 #[derive(Debug, Default)]
 pub(crate) struct Attrs {
     pub attrs: HashMap<Ident, Type>,
-    pub modname: Option<Ident>
+    pub modname: Option<Ident>,
+    /// Keep the expectations of static methods in thread-local storage
+    pub thread_local: bool
 }
 
 impl Attrs {
@@ -251,6 +275,7 @@ impl Parse for Attrs {
     fn parse(input: ParseStream) -> parse::Result<Self> {
         let mut attrs = HashMap::new();
         let mut modname = None;
+        let mut thread_local = false;
         while !input.is_empty() {
             let attr: Attr = input.parse()?;
             match attr {
@@ -261,6 +286,9 @@ impl Parse for Attrs {
                     }
                     modname = Some(item_mod.ident.clone());
                 },
+                Attr::Statics(tl) => {
+                    thread_local = tl;
+                },
                 Attr::Type(trait_item_type) => {
                     let ident = trait_item_type.ident.clone();
                     if let Some((_, ty)) = trait_item_type.default {
@@ -272,7 +300,7 @@ impl Parse for Attrs {
                 }
             }
         }
-        Ok(Attrs{attrs, modname})
+        Ok(Attrs{attrs, modname, thread_local})
     }
 }
 
@@ -295,6 +323,16 @@ mod t {
         assert_eq!(in_ty, expect_ty);
     }
 
+    #[test]
+    fn statics() {
+        let attrs: super::Attrs =
+            parse2(quote!(statics = thread_local; type T = u32;)).unwrap();
+        assert!(attrs.thread_local);
+        let attrs: super::Attrs = parse2(quote!(statics = global)).unwrap();
+        assert!(!attrs.thread_local);
+        assert!(parse2::<super::Attrs>(quote!(statics = local)).is_err());
+    }
+
     #[test]
     fn qself() {
         check_substitute_type(quote!(type T = u32;),
@@ -1235,6 +1235,16 @@ pub fn concretize(
     input
 }
 
+#[proc_macro_attribute]
+pub fn real(
+    _attrs: proc_macro::TokenStream,
+    input: proc_macro::TokenStream) -> proc_macro::TokenStream
+{
+    // Do nothing.  This "attribute" is processed as text by the real proc
+    // macros.
+    input
+}
+
 #[proc_macro]
 pub fn mock(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     do_mock(input.into()).into()
@@ -1,6 +1,7 @@
 // vim: tw=80
 use super::*;
 
+use proc_macro2::TokenTree;
 use quote::ToTokens;
 
 /// Convert a trait object reference into a reference to a Boxed trait
@@ -32,6 +33,40 @@ fn dedynify(ty: &mut Type) -> bool {
     false
 }
 
+/// If `ty` is a boxed future, like `Pin<Box<dyn Future<Output=T> + Send>>`,
+/// return the trait object's bounds.
+fn future_bounds(ty: &Type) -> Option<&Punctuated<TypeParamBound, Token![+]>>
+{
+    /// If `ty` is `name<T>`, return `T`.
+    fn first_arg<'a>(ty: &'a Type, name: &str) -> Option<&'a Type> {
+        if let Type::Path(tp) = ty {
+            let seg = tp.path.segments.last()?;
+            if seg.ident == name {
+                if let PathArguments::AngleBracketed(abga) = &seg.arguments {
+                    if let Some(GenericArgument::Type(t)) = abga.args.first() {
+                        return Some(t);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    if let Type::TraitObject(tto) = first_arg(first_arg(ty, "Pin")?, "Box")? {
+        let is_future = tto.bounds.iter().any(|tpb| {
+            match tpb {
+                TypeParamBound::Trait(tb) => matches!(tb.path.segments.last(),
+                    Some(seg) if seg.ident == "Future"),
+                _ => false
+            }
+        });
+        if is_future {
+            return Some(&tto.bounds);
+        }
+    }
+    None
+}
+
 /// Convert a special reference type like "&str" into a reference to its owned
 /// type like "&String".
 fn destrify(ty: &mut Type) {
@@ -97,6 +132,91 @@ fn destrify(ty: &mut Type) {
     }
 }
 
+/// Return the type that a method's call history uses to store an argument of
+/// type `ty`, or `None` if such an argument can't be stored in the mock object.
+///
+/// References are stored as their owned types, like `String` for `&str`.
+/// Storing is impossible for trait objects and other unsized types, for types
+/// that contain non-`'static` lifetimes, and for types that use any of the
+/// expectation's generic parameters.
+fn history_type(ty: &Type, generics: &Generics) -> Option<Type> {
+    fn is_storable(ts: TokenStream, tparams: &[&Ident]) -> bool {
+        let mut tts = ts.into_iter().peekable();
+        while let Some(tt) = tts.next() {
+            match tt {
+                TokenTree::Group(g) if !is_storable(g.stream(), tparams) => {
+                    return false;
+                },
+                TokenTree::Ident(i) if tparams.contains(&&i) => {
+                    return false;
+                },
+                TokenTree::Punct(p) if p.as_char() == '&' => {
+                    // Only 'static references may be nested within the type
+                    if let Some(TokenTree::Punct(p2)) = tts.peek() {
+                        if p2.as_char() != '\'' {
+                            return false;
+                        }
+                    } else {
+                        return false;
+                    }
+                },
+                TokenTree::Punct(p) if p.as_char() == '\'' => {
+                    match tts.next() {
+                        Some(TokenTree::Ident(i)) if i == "static" => (),
+                        _ => return false
+                    }
+                },
+                _ => ()
+            }
+        }
+        true
+    }
+
+    let owned = if let Type::Reference(tr) = ty {
+        let mut r = Type::Reference(TypeReference {
+            and_token: tr.and_token,
+            lifetime: None,
+            mutability: None,
+            elem: tr.elem.clone()
+        });
+        destrify(&mut r);
+        ownify(&r)
+    } else {
+        ty.clone()
+    };
+    match &owned {
+        Type::Array(_) | Type::BareFn(_) | Type::Path(_) | Type::Ptr(_) |
+            Type::Tuple(_) => (),
+        _ => return None
+    };
+    let tparams = generics.type_params()
+        .map(|tp| &tp.ident)
+        .collect::<Vec<_>>();
+    if is_storable(owned.to_token_stream(), &tparams) {
+        Some(owned)
+    } else {
+        None
+    }
+}
+
+/// Generate an expression that formats an argument with `{:?}`, using its
+/// `Debug` impl if it has one.
+fn debug_arg(argname: &Pat) -> TokenStream {
+    quote!({
+        use ::mockall::{ViaDebug as _, ViaMaybeDebug as _};
+        (&&::mockall::MaybeDebugger(&#argname)).debug_arg()
+    })
+}
+
+/// Does the token stream contain the given identifier anywhere?
+fn mentions_ident(ts: TokenStream, ident: &str) -> bool {
+    ts.into_iter().any(|tt| match tt {
+        TokenTree::Group(g) => mentions_ident(g.stream(), ident),
+        TokenTree::Ident(i) => i == ident,
+        _ => false
+    })
+}
+
 /// Return the owned version of the input.
 fn ownify(ty: &Type) -> Type {
     if let Type::Reference(ref tr) = &ty {
@@ -154,9 +274,11 @@ pub(crate) struct Builder<'a> {
     concretize: bool,
     levels: usize,
     parent: Option<&'a Ident>,
+    passthrough: bool,
     sig: &'a Signature,
     struct_: Option<&'a Ident>,
     struct_generics: Option<&'a Generics>,
+    thread_local: bool,
     trait_: Option<&'a Ident>,
     vis: &'a Visibility
 }
@@ -178,6 +300,7 @@ impl<'a> Builder<'a> {
     pub fn build(self) -> MockFunction {
         let mut argnames = Vec::new();
         let mut argty = Vec::new();
+        let mut call_argty = Vec::new();
         let mut is_static = true;
         let mut predexprs = Vec::new();
         let mut predty = Vec::new();
@@ -223,6 +346,7 @@ impl<'a> Builder<'a> {
                 };
                 argnames.push(argname);
                 argty.push(aty.clone());
+                call_argty.push((*pt.ty).clone());
             } else {
                 is_static = false;
             }
@@ -242,6 +366,9 @@ impl<'a> Builder<'a> {
                 (output_ty, boxed)
             }
         };
+        let mut outer_declosured_generics = declosured_generics.clone();
+        supersuperfy_generics(&mut outer_declosured_generics,
+            self.call_levels.unwrap_or(self.levels));
         supersuperfy_generics(&mut declosured_generics, self.levels);
         let owned_output = ownify(&output);
         let mut return_ref = false;
@@ -276,6 +403,12 @@ impl<'a> Builder<'a> {
                               Box::new(owned_output.clone()))
         );
         let mrltg = lifetimes_to_generics(&mrlifetimes);
+        let (outer_call_generics, _, _) = split_lifetimes(
+            outer_declosured_generics,
+            &declosured_inputs,
+            &ReturnType::Type(<Token![->]>::default(),
+                              Box::new(owned_output.clone()))
+        );
         let cgenerics = merge_generics(&type_generics, &call_generics);
         let egenerics = merge_generics(
             &merge_generics(&cgenerics, &srltg),
@@ -290,7 +423,38 @@ impl<'a> Builder<'a> {
         let fn_params = egenerics.type_params()
             .map(|tp| tp.ident.clone())
             .collect();
+        let call_histty = call_argty.iter()
+            .map(|ty| history_type(ty, &egenerics))
+            .collect::<Vec<_>>();
+        let histty = call_histty.iter()
+            .map(|ty| ty.as_ref().map(|ty| supersuperfy(ty, self.levels)))
+            .collect();
+        let histrefs = call_argty.iter()
+            .map(|ty| matches!(ty, Type::Reference(_)))
+            .collect();
         let call_levels = self.call_levels.unwrap_or(self.levels);
+        // A real object can't implement methods that mention the mock struct
+        let mentions_self = if let Some(s) = self.struct_ {
+            let mut ts = self.sig.inputs.to_token_stream();
+            self.sig.output.to_tokens(&mut ts);
+            mentions_ident(ts, &s.to_string())
+        } else {
+            false
+        };
+        let passthrough = self.passthrough
+            && !is_static
+            && !self.concretize
+            && self.sig.asyncness.is_none()
+            && self.sig.variadic.is_none()
+            && call_generics.type_params().next().is_none()
+            && call_generics.const_params().next().is_none()
+            && call_generics.where_clause.is_none()
+            && matches!(self.sig.inputs.first(),
+                        Some(FnArg::Receiver(r)) if r.reference.is_some())
+            // deanonymize may have replaced '_ with 'static in the return
+            // type, which the real method wouldn't return
+            && !mentions_ident(self.sig.output.to_token_stream(), "static")
+            && !mentions_self;
 
         MockFunction {
             alifetimes,
@@ -299,16 +463,22 @@ impl<'a> Builder<'a> {
             attrs: self.attrs.to_vec(),
             call_exprs,
             call_generics,
+            outer_call_generics,
+            call_histty,
             call_vis: expectation_visibility(self.vis, call_levels),
             concretize: self.concretize,
             egenerics,
             cgenerics,
             fn_params,
+            histrefs,
+            histty,
             is_static,
+            levels: self.levels,
             mod_ident: self.parent.unwrap_or(&Ident::new("FIXME", Span::call_site())).clone(),
             output,
             owned_output,
             boxed,
+            passthrough,
             predexprs,
             predty,
             refpredty,
@@ -317,6 +487,7 @@ impl<'a> Builder<'a> {
             sig: self.sig.clone(),
             struct_: self.struct_.cloned(),
             struct_generics,
+            thread_local: self.thread_local,
             trait_: self.trait_.cloned(),
             type_generics,
             privmod_vis: expectation_visibility(self.vis, self.levels)
@@ -348,9 +519,11 @@ impl<'a> Builder<'a> {
             levels: 0,
             call_levels: None,
             parent: None,
+            passthrough: false,
             sig,
             struct_: None,
             struct_generics: None,
+            thread_local: false,
             trait_: None,
             vis
         }
@@ -362,6 +535,13 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Allow calls that match no expectation to be forwarded to a wrapped real
+    /// object, if the method's signature permits it.
+    pub fn passthrough(&mut self, passthrough: bool) -> &mut Self {
+        self.passthrough = passthrough;
+        self
+    }
+
     /// Supply the name of the parent struct, if any
     pub fn struct_(&mut self, ident: &'a Ident) -> &mut Self {
         self.struct_= Some(ident);
@@ -374,6 +554,13 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Keep a static method's expectations in thread-local storage instead of
+    /// a global
+    pub fn thread_local(&mut self, thread_local: bool) -> &mut Self {
+        self.thread_local = thread_local;
+        self
+    }
+
     /// Supply the name of the method's trait, if any
     pub fn trait_(&mut self, ident: &'a Ident) -> &mut Self {
         self.trait_ = Some(ident);
@@ -396,6 +583,12 @@ pub(crate) struct MockFunction {
     call_exprs: Vec<TokenStream>,
     /// Generics used for the expectation call
     call_generics: Generics,
+    /// Like `call_generics`, but for use at the mock function's own level
+    /// rather than within its private module
+    outer_call_generics: Generics,
+    /// Types used to record each argument in the call history, as seen from
+    /// the mock function itself.  `None` for arguments that can't be stored.
+    call_histty: Vec<Option<Type>>,
     /// Visibility of the mock function itself
     call_vis: Visibility,
     /// Are we turning generic arguments into concrete trait objects?
@@ -406,8 +599,15 @@ pub(crate) struct MockFunction {
     cgenerics: Generics,
     /// The mock function's generic types as a list of types
     fn_params: Vec<Ident>,
+    /// Is each argument a reference?  Used when recording the call history.
+    histrefs: Vec<bool>,
+    /// Types used to record each argument in the call history, supersuperfied
+    histty: Vec<Option<Type>>,
     /// Is this for a static method or free function?
     is_static: bool,
+    /// How many levels of modules beneath the mock the private module is
+    /// nested
+    levels: usize,
     /// name of the function's parent module
     mod_ident: Ident,
     /// Output type of the Method, supersuperfied.
@@ -419,6 +619,8 @@ pub(crate) struct MockFunction {
     owned_output: Type,
     /// True if the `owned_type` is boxed by `Box<>`.
     boxed: bool,
+    /// Can unmatched calls be forwarded to a wrapped real object?
+    passthrough: bool,
     /// Expressions that create the predicate arguments from the call arguments
     predexprs: Vec<TokenStream>,
     /// Types used for Predicates.  Will be almost the same as args, but every
@@ -436,6 +638,8 @@ pub(crate) struct MockFunction {
     struct_: Option<Ident>,
     /// Generics of the parent structure
     struct_generics: Generics,
+    /// Are a static method's expectations thread-local?
+    thread_local: bool,
     /// Name of this method's trait, if the method comes from a trait
     trait_: Option<Ident>,
     /// Type generics of the mock structure
@@ -462,9 +666,13 @@ impl MockFunction {
         }.split_for_impl();
         let tbf = tg.as_turbofish();
         let name = self.name();
-        let desc = self.desc();
+        let desc = self.desc(quote!(self.__mockall_name.as_deref()));
         let no_match_msg = quote!(std::format!(
-            "{}: No matching expectation found", #desc));
+            "{}: No matching expectation found (called at {})", #desc,
+            ::std::panic::Location::caller()));
+        let track_caller = self.track_caller();
+        let record_exprs = self.record_exprs();
+        let passthrough = self.passthrough_call();
         let sig = &self.sig;
         let (vis, dead_code) = if self.trait_.is_some() {
             (&Visibility::Inherited, quote!())
@@ -499,37 +707,97 @@ impl MockFunction {
                 deref = quote!(&mut **);
             }
         }
+        let resolve = if self.is_async() {
+            quote!(.resolve().await)
+        } else {
+            quote!()
+        };
+        let predexprs = &self.predexprs;
         if self.is_static {
             let outer_mod_path = self.outer_mod_path(modname);
+            let expectations = self.expectations_mutex(
+                quote!(#outer_mod_path::));
+            let static_turn = if self.awaits_turn() {
+                let wait = if self.is_async() {
+                    quote!(__mockall_turn.await;)
+                } else {
+                    quote!(__mockall_turn.wait();)
+                };
+                quote!(
+                    // Don't hold the lock while waiting
+                    let __mockall_turn = #expectations
+                        .lock()
+                        .unwrap_or_else(::std::sync::PoisonError::into_inner)
+                        .turn#tbf(#(#predexprs,)*);
+                    #wait
+                )
+            } else {
+                quote!()
+            };
             quote!(
                 // Don't add a doc string.  The original is included in #attrs
                 #(#attrs)*
                 #dead_code
+                #track_caller
                 #vis #sig {
                     let no_match_msg = #no_match_msg;
+                    #static_turn
                     #deref {
-                        let __mockall_guard = #outer_mod_path::EXPECTATIONS
-                            .lock().unwrap();
-                        /*
-                         * TODO: catch panics, then gracefully release the mutex
-                         * so it won't be poisoned.  This requires bounding any
-                         * generic parameters with UnwindSafe
-                         */
-                        /* std::panic::catch_unwind(|| */
+                        // If an earlier call panicked while holding the lock,
+                        // for example in a `returning` closure, the mutex is
+                        // poisoned.  But the expectations are still usable, so
+                        // recover rather than failing every later call.
+                        let __mockall_e = &#expectations;
+                        let __mockall_guard = __mockall_e
+                            .lock()
+                            .unwrap_or_else(::std::sync::PoisonError::into_inner);
+                        if __mockall_guard.recording() {
+                            __mockall_guard.record((#(#record_exprs,)*));
+                        }
                         __mockall_guard.#call#tbf(#(#call_exprs,)*)
-                        /*)*/
-                    }.expect(&no_match_msg)
+                    }.unwrap_or_else(|__mockall_nm| __mockall_nm.fail(no_match_msg))
+                    #resolve
                 }
             )
         } else {
+            let default = if self.return_refmut {
+                // The call's mutable borrow of self is still live here
+                quote!(|| ::std::result::Result::Err(
+                    "it returns by mutable reference"))
+            } else {
+                quote!(|| self.#substruct_obj #name.default_output#tbf())
+            };
+            let (flavor, fail) = if self.is_named() {
+                (quote!(let __mockall_flavor = self.__mockall_flavor;),
+                 quote!(__mockall_nm.fail_or_default(__mockall_flavor,
+                                                     no_match_msg,
+                                                     #default)))
+            } else {
+                (quote!(), quote!(__mockall_nm.fail(no_match_msg)))
+            };
+            let turn = if self.awaits_turn() && self.is_async() {
+                quote!(
+                    self.#substruct_obj #name.turn#tbf(#(#predexprs,)*).await;
+                )
+            } else {
+                quote!()
+            };
             quote!(
                 // Don't add a doc string.  The original is included in #attrs
                 #(#attrs)*
                 #dead_code
+                #track_caller
                 #vis #sig {
                     let no_match_msg = #no_match_msg;
+                    #flavor
+                    #turn
+                    if self.#substruct_obj #name.recording() {
+                        self.#substruct_obj #name.record((#(#record_exprs,)*));
+                    }
+                    #passthrough
                     #deref self.#substruct_obj #name.#call#tbf(#(#call_exprs,)*)
-                    .expect(&no_match_msg)
+                    .unwrap_or_else(|__mockall_nm| #fail)
+                    #resolve
                 }
 
             )
@@ -543,11 +811,13 @@ impl MockFunction {
             .format();
         let inner_mod_ident = self.inner_mod_ident();
         if self.is_static {
+            let expectations = self.expectations_mutex(
+                quote!(#inner_mod_ident::));
             quote!(
                 #(#attrs)*
                 {
-                    let __mockall_timeses = #inner_mod_ident::EXPECTATIONS.lock()
-                        .unwrap()
+                    let __mockall_timeses = #expectations.lock()
+                        .unwrap_or_else(::std::sync::PoisonError::into_inner)
                         .checkpoint()
                         .collect::<Vec<_>>();
                 }
@@ -558,6 +828,61 @@ impl MockFunction {
         }
     }
 
+    /// Return this method's contribution to its parent's match_order method
+    pub fn match_order(&self) -> impl ToTokens {
+        let attrs = AttrFormatter::new(&self.attrs)
+            .doc(false)
+            .format();
+        let name = &self.name();
+        quote!(#(#attrs)* self.#name.match_order(__mockall_order);)
+    }
+
+    /// Return this method's contribution to its parent's unsatisfied method
+    pub fn unsatisfied(&self) -> impl ToTokens {
+        let attrs = AttrFormatter::new(&self.attrs)
+            .doc(false)
+            .format();
+        let name = &self.name();
+        quote!(
+            #(#attrs)*
+            __mockall_unsatisfied.extend(self.#name.unsatisfied());
+        )
+    }
+
+    /// Generate code for the calls_ method, which returns the call history
+    ///
+    /// # Arguments
+    ///
+    /// * `modname`:    Name of the parent struct's private module
+    // Supplying modname is an unfortunately hack.  Ideally MockFunction
+    // wouldn't need to know that.
+    pub fn calls_fn(&self, modname: &Ident) -> impl ToTokens {
+        let attrs = AttrFormatter::new(&self.attrs)
+            .doc(false)
+            .format();
+        let name = self.name();
+        let calls_ident = format_ident!("calls_{}", name);
+        let inner_mod_ident = self.inner_mod_ident();
+        let substruct_obj = if let Some(trait_) = &self.trait_ {
+            let ident = format_ident!("{}_expectations", trait_);
+            quote!(#ident.)
+        } else {
+            quote!()
+        };
+        let docstr = format!("Return the arguments of every call made to the `{}` method so far, once the call history is turned on.  See [`Call history`](../mockall/index.html#call-history).",
+            name);
+        let vis = &self.call_vis;
+        quote!(
+            #[doc = #docstr]
+            #(#attrs)*
+            #vis fn #calls_ident(&self)
+                -> ::std::vec::Vec<#modname::#inner_mod_ident::Call>
+            {
+                self.#substruct_obj #name.calls()
+            }
+        )
+    }
+
     /// Return a function that creates a Context object for this function
     ///
     /// # Arguments
@@ -587,17 +912,214 @@ impl MockFunction {
         )
     }
 
+    /// Return this static method's field definition within its mock's
+    /// `StaticContext`.
+    ///
+    /// # Arguments
+    ///
+    /// * `modname`:    Name of the parent struct's private module
+    pub fn static_context_field(&self, modname: Option<&Ident>)
+        -> impl ToTokens
+    {
+        let attrs = AttrFormatter::new(&self.attrs)
+            .doc(false)
+            .format();
+        let name = self.name();
+        let (_, tg, _) = self.type_generics.split_for_impl();
+        let outer_mod_path = self.outer_mod_path(modname);
+        quote!(#(#attrs)* #name: #outer_mod_path::Context #tg)
+    }
+
+    /// Return a fragment of code to initialize this static method's field
+    /// within its mock's `StaticContext`.
+    pub fn static_context_init(&self) -> impl ToTokens {
+        let attrs = AttrFormatter::new(&self.attrs)
+            .doc(false)
+            .format();
+        let name = self.name();
+        let context_ident = format_ident!("{}_context", name);
+        // A mock struct's context functions are associated functions, but a
+        // mocked module's are free functions.
+        let prefix = self.struct_.as_ref().map(|_| quote!(Self::));
+        quote!(#(#attrs)* #name: #prefix #context_ident())
+    }
+
+    /// Return this static method's contribution to its mock's
+    /// `StaticContext::checkpoint` method.
+    pub fn static_context_checkpoint(&self) -> impl ToTokens {
+        let attrs = AttrFormatter::new(&self.attrs)
+            .doc(false)
+            .format();
+        let name = self.name();
+        quote!(#(#attrs)* self.#name.checkpoint();)
+    }
+
+    /// Generate the `expect_foo` and `on_call_foo` methods of this static
+    /// method's mock's `StaticContext`.
+    ///
+    /// # Arguments
+    ///
+    /// * `modname`:    Name of the parent struct's private module
+    pub fn static_context_methods(&self, modname: Option<&Ident>)
+        -> impl ToTokens
+    {
+        let attrs = AttrFormatter::new(&self.attrs)
+            .doc(false)
+            .format();
+        let name = self.name();
+        let expect_ident = format_ident!("expect_{}", name);
+        let on_call_ident = format_ident!("on_call_{}", name);
+        let outer_mod_path = self.outer_mod_path(modname);
+        let lt = Lifetime::new("'__mockall_lt", Span::call_site());
+        let mut egenerics = self.egenerics.clone();
+        egenerics.lt_token.get_or_insert(<Token![<]>::default());
+        egenerics.params.push(GenericParam::Lifetime(
+            LifetimeDef::new(lt.clone())));
+        egenerics.gt_token.get_or_insert(<Token![>]>::default());
+        let (_, e_tg, _) = egenerics.split_for_impl();
+        let mut meth_generics = self.outer_call_generics.clone();
+        meth_generics.params.push(GenericParam::Lifetime(LifetimeDef::new(lt)));
+        let (meth_ig, _, meth_wc) = meth_generics.split_for_impl();
+        let v = &self.call_vis;
+        let expect_docstr = format!("Create a new expectation for the `{}` method.", name);
+        let on_call_docstr = format!("Set a default action for the `{}` method, used when no expectation matches.  It has no call count requirement of its own.", name);
+
+        quote!(
+            #[doc = #expect_docstr]
+            #(#attrs)*
+            #[track_caller]
+            #v fn #expect_ident #meth_ig (&self)
+                -> #outer_mod_path::ExpectationGuard #e_tg
+                #meth_wc
+            {
+                self.#name.expect()
+            }
+
+            #[doc = #on_call_docstr]
+            #(#attrs)*
+            #[track_caller]
+            #v fn #on_call_ident #meth_ig (&self)
+                -> #outer_mod_path::ExpectationGuard #e_tg
+                #meth_wc
+            {
+                self.#name.on_call()
+            }
+        )
+    }
+
+    /// Generate this method's contribution to the trait that forwards calls to
+    /// a wrapped real object.
+    ///
+    /// Returns the method's declaration within that trait, and its
+    /// implementation for any type that implements `trait_path`.
+    pub fn passthrough_method(&self, trait_path: &Path)
+        -> (TokenStream, TokenStream)
+    {
+        let attrs = self.attrs.iter()
+            .filter(|attr| attr.path.is_ident("cfg"))
+            .collect::<Vec<_>>();
+        let argnames = &self.argnames;
+        let name = self.name();
+        let mut sig = self.sig.clone();
+        sig.constness = None;
+        sig.ident = self.passthrough_ident();
+        (
+            quote!(#(#attrs)* #sig;),
+            quote!(
+                #(#attrs)*
+                #sig {
+                    <__MockallReal as #trait_path>::#name(self, #(#argnames),*)
+                }
+            )
+        )
+    }
+
+    /// Generate code that forwards the call to the wrapped real object, if
+    /// there is one and no expectation matches the call.
+    fn passthrough_call(&self) -> TokenStream {
+        if !self.passthrough {
+            return TokenStream::new();
+        }
+        let argnames = &self.argnames;
+        let name = self.name();
+        let predexprs = &self.predexprs;
+        let ident = self.passthrough_ident();
+        let substruct_obj = format_ident!("{}_expectations",
+                                          self.trait_.as_ref().unwrap());
+        let as_ref = match self.sig.inputs.first() {
+            Some(FnArg::Receiver(r)) if r.mutability.is_some() =>
+                quote!(as_mut),
+            _ => quote!(as_ref)
+        };
+        quote!(
+            if self.__mockall_real.is_some() &&
+                !self.#substruct_obj.#name.matches(#(#predexprs, )*)
+            {
+                return self.__mockall_real.#as_ref().unwrap()
+                    .#ident(#(#argnames),*);
+            }
+        )
+    }
+
+    /// Name of this method within the trait that forwards calls to a wrapped
+    /// real object
+    fn passthrough_ident(&self) -> Ident {
+        format_ident!("{}_{}", self.trait_.as_ref().unwrap(), self.name())
+    }
+
+    /// Generate code fragments that record each argument for the call history
+    fn record_exprs(&self) -> Vec<TokenStream> {
+        self.argnames.iter()
+            .zip(self.call_histty.iter())
+            .zip(self.histrefs.iter())
+            .map(|((argname, histty), isref)| {
+                let printer = debug_arg(argname);
+                let debug = quote!(std::format!("{:?}", #printer));
+                if let Some(ty) = histty {
+                    let input = if *isref {
+                        quote!(&*#argname)
+                    } else {
+                        quote!(&#argname)
+                    };
+                    quote!({
+                        use ::mockall::{
+                            RecordDebug as _,
+                            RecordOwned as _,
+                            RecordOwnedDebug as _
+                        };
+                        (&&::mockall::ArgRecorder::<_, #ty>::new(#input))
+                            .record(|| #debug)
+                    })
+                } else {
+                    quote!(::mockall::Arg::<()>::unrecorded(#debug))
+                }
+            }).collect()
+    }
+
     /// Generate a code fragment that will print a description of the invocation
-    fn desc(&self) -> impl ToTokens {
+    /// `instance` is an expression for the mock object's name, as an
+    /// `Option<&str>`.  It's only used by methods that can have one.
+    fn desc(&self, instance: TokenStream) -> impl ToTokens {
         let argnames = &self.argnames;
         let name = if let Some(s) = &self.struct_ {
             format!("{}::{}", s, self.sig.ident)
         } else {
             format!("{}::{}", self.mod_ident, self.sig.ident)
         };
+        let printers = argnames.iter().map(debug_arg).collect::<Vec<_>>();
         let fields = vec!["{:?}"; argnames.len()].join(", ");
         let fstr = format!("{}({})", name, fields);
-        quote!(std::format!(#fstr, #(::mockall::MaybeDebugger(&#argnames)),*))
+        if let Some(s) = self.struct_.as_ref().filter(|_| self.is_named()) {
+            let named_fstr = format!("{}[{{}}]::{}({})", s, self.sig.ident,
+                                     fields);
+            quote!(match #instance {
+                Some(__mockall_n) => std::format!(#named_fstr, __mockall_n,
+                    #(#printers),*),
+                None => std::format!(#fstr, #(#printers),*)
+            })
+        } else {
+            quote!(std::format!(#fstr, #(#printers),*))
+        }
     }
 
     /// Generate code for the expect_ method
@@ -635,13 +1157,6 @@ impl MockFunction {
         let tbf = tg.as_turbofish();
         let vis = &self.call_vis;
 
-        #[cfg(not(feature = "nightly_derive"))]
-        let must_use = quote!(#[must_use =
-                "Must set return value when not using the \"nightly\" feature"
-            ]);
-        #[cfg(feature = "nightly_derive")]
-        let must_use = quote!();
-
         let substruct_obj = if let Some(trait_) = &self.trait_ {
             let ident = format_ident!("{}_expectations", trait_);
             quote!(#ident.)
@@ -650,15 +1165,53 @@ impl MockFunction {
         };
         let docstr = format!("Create an [`Expectation`]({}/{}/struct.Expectation.html) for mocking the `{}` method",
             modname, self.inner_mod_ident(), funcname);
+        let on_call_ident = format_ident!("on_call_{}", &name);
+        let on_call_docstr = format!("Set a default action for the `{}` method, used when no [`Expectation`]({}/{}/struct.Expectation.html) matches.  It has no call count requirement of its own.",
+            funcname, modname, self.inner_mod_ident());
+        let match_order_ident = format_ident!("match_order_{}", &name);
+        let match_order_docstr = format!("Set the order in which the `{}` method's expectations are matched.  See [`MatchOrder`](::mockall::MatchOrder).",
+            funcname);
+        let (expect_body, on_call_body) = if self.is_named() {
+            (quote!(
+                self.#substruct_obj #name.expect#tbf()
+                    .mock_name(self.__mockall_name.as_deref())
+                    .flavor(self.__mockall_flavor)
+            ), quote!(
+                self.#substruct_obj #name.on_call#tbf()
+                    .mock_name(self.__mockall_name.as_deref())
+                    .flavor(self.__mockall_flavor)
+            ))
+        } else {
+            (quote!(self.#substruct_obj #name.expect#tbf()),
+             quote!(self.#substruct_obj #name.on_call#tbf()))
+        };
         quote!(
-            #must_use
             #[doc = #docstr]
             #(#attrs)*
+            #[track_caller]
             #vis fn #expect_ident #ig(&mut self)
                -> &mut #modname::#expectation_obj
                #wc
             {
-                self.#substruct_obj #name.expect#tbf()
+                #expect_body
+            }
+
+            #[doc = #on_call_docstr]
+            #(#attrs)*
+            #[track_caller]
+            #vis fn #on_call_ident #ig(&mut self)
+               -> &mut #modname::#expectation_obj
+               #wc
+            {
+                #on_call_body
+            }
+
+            #[doc = #match_order_docstr]
+            #(#attrs)*
+            #vis fn #match_order_ident(&mut self,
+                order: ::mockall::MatchOrder)
+            {
+                self.#substruct_obj #name.match_order(order);
             }
         )
     }
@@ -728,6 +1281,48 @@ impl MockFunction {
         }
     }
 
+    /// Define the `EXPECTATIONS` static that holds a static method's
+    /// expectations of type `ty`.
+    fn expectations_static(&self, ty: TokenStream) -> TokenStream {
+        let v = &self.privmod_vis;
+        if self.thread_local {
+            // A thread-local can't be borrowed beyond a `LocalKey::with`
+            // closure, so each access clones an `Rc` to it instead.  See
+            // `expectations_mutex`.
+            quote!(
+                ::std::thread_local! {
+                    #[doc(hidden)]
+                    #v static EXPECTATIONS:
+                        ::std::rc::Rc<::std::sync::Mutex<#ty>> =
+                        ::std::rc::Rc::new(
+                            ::std::sync::Mutex::new(<#ty>::new())
+                        );
+                }
+            )
+        } else {
+            quote!(
+                ::mockall::lazy_static! {
+                    #[doc(hidden)]
+                    #v static ref EXPECTATIONS: ::std::sync::Mutex<#ty> =
+                        ::std::sync::Mutex::new(<#ty>::new());
+                }
+            )
+        }
+    }
+
+    /// An expression for the `Mutex` that holds a static method's
+    /// expectations, defined by `expectations_static` in the module at `path`.
+    ///
+    /// In thread-local mode it's a temporary, so bind a reference to it before
+    /// holding its lock beyond one statement.
+    fn expectations_mutex(&self, path: TokenStream) -> TokenStream {
+        if self.thread_local {
+            quote!(#path EXPECTATIONS.with(::std::rc::Rc::clone))
+        } else {
+            quote!(#path EXPECTATIONS)
+        }
+    }
+
     fn hrtb(&self) -> Option<BoundLifetimes> {
         if self.alifetimes.is_empty() {
             None
@@ -769,14 +1364,92 @@ impl MockFunction {
         format_ident!("__{}", &self.name())
     }
 
+    /// Can calls that match no expectation be forwarded to a wrapped real
+    /// object?
+    pub fn is_passthrough(&self) -> bool {
+        self.passthrough
+    }
+
     pub fn is_static(&self) -> bool {
         self.is_static
     }
 
+    /// Is this an `async fn` that returns a `'static` value?  Its expectation
+    /// may return a future, which the mock method must `.await`.
+    fn is_async(&self) -> bool {
+        self.sig.asyncness.is_some() && !self.return_ref && !self.return_refmut
+    }
+
+    /// Should the mock method wait for its expectation's turn in a rendezvous
+    /// `Sequence` before calling it?  `async fn`s wait so they won't block the
+    /// executor, and static methods so they won't hold the lock on their
+    /// expectations.  Only possible for methods whose arguments are passed to
+    /// the expectation unchanged, because the expectation must be matched
+    /// before the call.
+    fn awaits_turn(&self) -> bool {
+        (self.is_async() || self.is_static) &&
+            self.argnames.iter().zip(self.call_exprs.iter())
+            .all(|(argname, call_expr)|
+                 quote!(#argname).to_string() == call_expr.to_string())
+    }
+
+    /// The generics and type of the `Sequence` that `in_sequence` accepts.  A
+    /// static method that can't wait for its turn before locking its
+    /// expectations would lock out the very call it's waiting for, so it only
+    /// accepts a Sequence that doesn't wait.
+    fn seq_generics(&self) -> (TokenStream, TokenStream) {
+        if self.is_static && !self.awaits_turn() {
+            (quote!(), quote!(::mockall::Sequence<::mockall::Immediate>))
+        } else {
+            (quote!(<MockallK>), quote!(::mockall::Sequence<MockallK>))
+        }
+    }
+
+    /// Can this method's mock object be given a name, for failure messages?
+    fn is_named(&self) -> bool {
+        self.struct_.is_some() && !self.is_static
+    }
+
     pub fn name(&self) -> &Ident {
         &self.sig.ident
     }
 
+    /// Bounds for the future returned by a `returning_async` closure, if the
+    /// method can have one.
+    fn returning_async_bounds(&self) -> Option<TokenStream> {
+        let output = &self.output;
+        if self.is_async() {
+            Some(quote!(
+                ::std::future::Future<Output=#output> + Send + 'static
+            ))
+        } else if self.return_ref || self.return_refmut {
+            None
+        } else {
+            future_bounds(output).map(|bounds| quote!(#bounds + 'static))
+        }
+    }
+
+    /// The type returned by the expectation's `Rfunc`.
+    fn rfunc_output(&self) -> TokenStream {
+        let output = &self.output;
+        if self.is_async() {
+            quote!(::mockall::AsyncOutput<#output>)
+        } else {
+            quote!(#output)
+        }
+    }
+
+    /// `#[track_caller]`, if the mock function can have it.
+    fn track_caller(&self) -> TokenStream {
+        if self.sig.asyncness.is_none() && self.sig.variadic.is_none() &&
+            self.sig.abi.is_none()
+        {
+            quote!(#[track_caller])
+        } else {
+            quote!()
+        }
+    }
+
     /// Generate code for this function's private module
     pub fn priv_module(&self) -> impl ToTokens {
         let attrs = AttrFormatter::new(&self.attrs)
@@ -805,12 +1478,18 @@ impl MockFunction {
             Box::new(ConcreteExpectationGuard{f: self})
         };
         let matcher = &Matcher{f: self};
-        let std_mutexguard = if self.is_static {
+        let std_mutexguard = if self.is_static && !self.thread_local {
             quote!(use ::std::sync::MutexGuard;)
         } else {
             quote!()
         };
         let inner_mod_ident = self.inner_mod_ident();
+        let histty = self.histty.iter()
+            .map(|ty| ty.as_ref()
+                 .map(|ty| ty.to_token_stream())
+                 .unwrap_or_else(|| quote!(())))
+            .collect::<Vec<_>>();
+        let v = &self.privmod_vis;
         let rfunc: Box<dyn ToTokens> = if self.return_ref {
             Box::new(RefRfunc{f: self})
         } else if self.return_refmut {
@@ -832,6 +1511,9 @@ impl MockFunction {
                     sync::Mutex,
                     vec::Vec,
                 };
+                /// The arguments of a single call, as stored in the call
+                /// history.
+                #v type Call = (#(::mockall::Arg<#histty>,)*);
                 #rfunc
                 #matcher
                 #common
@@ -874,6 +1556,20 @@ impl<'a> ToTokens for Common<'a> {
         let boxed_withargs = argnames.iter()
             .map(|aa| quote!(Box::new(#aa), ))
             .collect::<TokenStream>();
+        let (seq_generics, seq_ty) = self.f.seq_generics();
+        let turn_method = if self.f.awaits_turn() {
+            quote!(
+                /// Wait for this expectation's turn in a rendezvous
+                /// [`Sequence`](::mockall::Sequence), if any.
+                fn turn(&self) -> ::mockall::SeqTurn {
+                    self.seq_handle.as_ref()
+                        .map(::mockall::SeqHandle::turn)
+                        .unwrap_or_default()
+                }
+            )
+        } else {
+            quote!()
+        };
         let with_method = if self.f.concretize {
             quote!(
                 // No `with` method when concretizing generics
@@ -882,74 +1578,226 @@ impl<'a> ToTokens for Common<'a> {
             quote!(
                 fn with<#with_generics>(&mut self, #with_args)
                     {
-                        let mut __mockall_guard = self.matcher.lock().unwrap();
-                        *__mockall_guard.deref_mut() =
-                            Matcher::Pred(Box::new((#boxed_withargs)));
+                        {
+                            let mut __mockall_guard = self.matcher.lock().unwrap();
+                            *__mockall_guard.deref_mut() =
+                                Matcher::Pred(Box::new((#boxed_withargs)));
+                        }
+                        self.update_sequence();
                     }
             )
         };
 
+        let (mock_name_field, mock_name_init, funcname_body) = if self.f.is_named() {
+            let named_funcname = format!("{}[{{}}]::{}",
+                self.f.struct_.as_ref().unwrap(), self.f.name());
+            (
+                quote!(
+                    /// Name of the mock object that owns the expectation
+                    mock_name: Option<::std::string::String>,
+                ),
+                quote!(mock_name: None,),
+                quote!(
+                    match &self.mock_name {
+                        Some(__mockall_n) => ::std::borrow::Cow::Owned(
+                            std::format!(#named_funcname, __mockall_n)),
+                        None => ::std::borrow::Cow::Borrowed(#funcname)
+                    }
+                )
+            )
+        } else {
+            (quote!(), quote!(), quote!(::std::borrow::Cow::Borrowed(#funcname)))
+        };
+
         quote!(
             /// Holds the stuff that is independent of the output type
             struct Common #ig #wc {
+                /// Closures that receive the arguments of every call
+                captures: Mutex<Vec<
+                    Box<dyn #hrtb FnMut(#( #refpredty, )*) + Send>
+                >>,
+                /// Identifies this expectation to those that must follow it
+                handle: Option<::mockall::ExpectationHandle>,
+                /// Where the expectation was created
+                location: Option<&'static ::std::panic::Location<'static>>,
                 matcher: Mutex<Matcher #tg>,
+                #mock_name_field
+                /// The expectation's own name, if any
+                name: Option<::std::string::String>,
+                /// Is this a default action, used only when no ordinary
+                /// expectation matches?
+                on_call: bool,
+                reporter: ::mockall::Reporter,
                 seq_handle: Option<::mockall::SeqHandle>,
-                times: ::mockall::Times
+                /// Does this expectation belong to a strict mock object?
+                strict: bool,
+                /// The state this expectation moves its StateMachine to
+                then_state: Option<::mockall::StateTransition>,
+                times: ::mockall::Times,
+                /// The only state in which this expectation may match
+                when_state: Option<::mockall::StateGuard>
             }
 
             impl #ig std::default::Default for Common #tg #wc
             {
                 fn default() -> Self {
                     Common {
+                        captures: Mutex::new(Vec::new()),
+                        handle: None,
+                        location: None,
                         matcher: Mutex::new(Matcher::default()),
+                        #mock_name_init
+                        name: None,
+                        on_call: false,
+                        reporter: ::mockall::Reporter::current(),
                         seq_handle: None,
-                        times: ::mockall::Times::default()
+                        strict: false,
+                        then_state: None,
+                        times: ::mockall::Times::default(),
+                        when_state: None
                     }
                 }
             }
 
             impl #ig Common #tg #wc {
+                #[track_caller]
                 fn call(&self, desc: &str) {
-                    self.times.call()
-                        .unwrap_or_else(|m| {
-                            let desc = std::format!(
-                                "{}", self.matcher.lock().unwrap());
-                            panic!("{}: Expectation({}) {}", #funcname, desc,
-                                m);
-                        });
-                    self.verify_sequence(desc);
-                    if self.times.is_satisfied() {
-                        self.satisfy_sequence()
+                    let __mockall_caller = ::std::panic::Location::caller();
+                    if let Err(m) = self.times.call() {
+                        self.reporter.fail(std::format!("{} {}{}",
+                            self.describe(), m,
+                            self.whence(Some(__mockall_caller))));
+                    }
+                    if self.strict && self.times.is_unbounded() {
+                        self.reporter.fail(std::format!(
+                            "{} has no call count, which strict mocks require{}",
+                            self.describe(),
+                            self.whence(Some(__mockall_caller))));
+                    }
+                    self.verify_sequence(desc, __mockall_caller);
+                    self.verify_order(desc, __mockall_caller);
+                    if let Some(__mockall_t) = &self.then_state {
+                        __mockall_t.apply();
                     }
+                    self.update_sequence();
+                    ::mockall::notify_call();
                 }
 
-                fn in_sequence(&mut self, __mockall_seq: &mut ::mockall::Sequence)
-                    -> &mut Self
+                /// Require another expectation to be satisfied before this one
+                /// may be called.
+                fn after(&mut self,
+                    __mockall_before: &::mockall::ExpectationHandle)
                 {
-                    assert!(self.times.is_exact(),
-                        "Only Expectations with an exact call count have sequences");
+                    self.handle().after(__mockall_before);
+                }
+
+                /// Pass a call's arguments to every `capturing` closure
+                #[allow(clippy::ptr_arg)]
+                fn capture #lg (&self, #( #argnames: &#predty, )*) {
+                    let mut __mockall_captures = self.captures.lock().unwrap();
+                    for __mockall_f in __mockall_captures.iter_mut() {
+                        __mockall_f(#(#argnames, )*);
+                    }
+                }
+
+                fn capturing<MockallF>(&mut self, __mockall_f: MockallF)
+                    where MockallF: #hrtb FnMut(#( #refpredty, )*)
+                                    + Send + 'static
+                {
+                    self.captures.lock().unwrap().push(Box::new(__mockall_f));
+                }
+
+                /// Is this expectation's StateMachine, if any, in the required
+                /// state?
+                fn check_state(&self)
+                    -> ::std::result::Result<(), ::std::string::String>
+                {
+                    match &self.when_state {
+                        Some(__mockall_g) => __mockall_g.check(),
+                        None => ::std::result::Result::Ok(())
+                    }
+                }
+
+                /// Describe this expectation for failure messages, like
+                /// `MockFoo::foo: Expectation(<anything>)`.
+                fn describe(&self) -> ::std::string::String {
+                    std::format!("{}: Expectation({}){}", self.funcname(),
+                        self.matcher.lock().unwrap(), self.label())
+                }
+
+                /// The name of the mocked method, including the name of its
+                /// mock object, if any.
+                fn funcname(&self) -> ::std::borrow::Cow<'static, str> {
+                    #funcname_body
+                }
+
+                fn in_sequence #seq_generics (&mut self,
+                    __mockall_seq: &mut #seq_ty) -> &mut Self
+                {
+                    assert!(self.times.is_bounded(),
+                        "Only Expectations with a bounded call count have sequences");
                     self.seq_handle = Some(__mockall_seq.next_handle());
+                    self.update_sequence();
                     self
                 }
 
+                /// Describe whether this expectation would handle a call with
+                /// these arguments, and if not then why not.
+                #[allow(clippy::ptr_arg)]
+                fn explain #lg (&self, #( #argnames: &#predty, )*) -> ::std::string::String {
+                    let __mockall_matcher = self.matcher.lock().unwrap();
+                    if !__mockall_matcher.matches(#(#argnames, )*) {
+                        std::format!("Expectation({}){} rejected the call{}",
+                            __mockall_matcher, self.label(),
+                            __mockall_matcher.explain(#(#argnames, )*))
+                    } else if let Err(__mockall_m) = self.check_state() {
+                        std::format!("Expectation({}){} matched, but {}",
+                            __mockall_matcher, self.label(), __mockall_m)
+                    } else if self.is_done() {
+                        std::format!(
+                            "Expectation({}){} matched, but is already saturated",
+                            __mockall_matcher, self.label())
+                    } else {
+                        std::format!("Expectation({}){} matched",
+                            __mockall_matcher, self.label())
+                    }
+                }
+
+                /// Identify this expectation, so others can be ordered after
+                /// it.
+                fn handle(&mut self) -> ::mockall::ExpectationHandle {
+                    if self.handle.is_none() {
+                        let __mockall_h = ::mockall::ExpectationHandle::new(
+                            std::format!("{}{}", self.funcname(),
+                                self.whence(None)));
+                        __mockall_h.set_satisfied(self.times.is_satisfied());
+                        self.handle = Some(__mockall_h);
+                    }
+                    self.handle.clone().unwrap()
+                }
+
                 fn is_done(&self) -> bool {
                     self.times.is_done()
                 }
 
+                /// The expectation's name, formatted for failure messages
+                fn label(&self) -> ::std::string::String {
+                    match &self.name {
+                        Some(__mockall_n) => std::format!(" named {:?}", __mockall_n),
+                        None => ::std::string::String::new()
+                    }
+                }
+
                 #[allow(clippy::ptr_arg)]
                 fn matches #lg (&self, #( #argnames: &#predty, )*) -> bool {
-                    self.matcher.lock().unwrap().matches(#(#argnames, )*)
+                    self.matcher.lock().unwrap().matches(#(#argnames, )*) &&
+                        self.check_state().is_ok()
                 }
 
                 /// Forbid this expectation from ever being called.
                 fn never(&mut self) {
                     self.times.never();
-                }
-
-                fn satisfy_sequence(&self) {
-                    if let Some(__mockall_handle) = &self.seq_handle {
-                        __mockall_handle.satisfy()
-                    }
+                    self.update_sequence();
                 }
 
                 /// Expect this expectation to be called any number of times
@@ -957,7 +1805,21 @@ impl<'a> ToTokens for Common<'a> {
                 fn times<MockallR>(&mut self, __mockall_r: MockallR)
                     where MockallR: Into<::mockall::TimesRange>
                 {
-                    self.times.times(__mockall_r)
+                    self.times.times(__mockall_r);
+                    self.update_sequence();
+                }
+
+                /// Tell the sequence, if any, and any expectations that must
+                /// follow this one, whether it has been called enough times to
+                /// move on.
+                fn update_sequence(&self) {
+                    if let Some(__mockall_handle) = &self.seq_handle {
+                        __mockall_handle.update(self.times.is_satisfied(),
+                            self.describe(), self.location);
+                    }
+                    if let Some(__mockall_handle) = &self.handle {
+                        __mockall_handle.set_satisfied(self.times.is_satisfied());
+                    }
                 }
 
                 #with_method
@@ -966,40 +1828,87 @@ impl<'a> ToTokens for Common<'a> {
                     where MockallF: #hrtb Fn(#( #refpredty, )*)
                                     -> bool + Send + 'static
                 {
-                    let mut __mockall_guard = self.matcher.lock().unwrap();
-                    *__mockall_guard.deref_mut() =
-                         Matcher::Func(Box::new(__mockall_f));
+                    {
+                        let mut __mockall_guard = self.matcher.lock().unwrap();
+                        *__mockall_guard.deref_mut() =
+                             Matcher::Func(Box::new(__mockall_f));
+                    }
+                    self.update_sequence();
                 }
 
                 fn withf_st<MockallF>(&mut self, __mockall_f: MockallF)
                     where MockallF: #hrtb Fn(#( #refpredty, )*)
                                     -> bool + 'static
                 {
-                    let mut __mockall_guard = self.matcher.lock().unwrap();
-                    *__mockall_guard.deref_mut() =
-                         Matcher::FuncSt(
-                             ::mockall::Fragile::new(Box::new(__mockall_f))
-                        );
+                    {
+                        let mut __mockall_guard = self.matcher.lock().unwrap();
+                        *__mockall_guard.deref_mut() =
+                             Matcher::FuncSt(
+                                 ::mockall::Fragile::new(Box::new(__mockall_f))
+                            );
+                    }
+                    self.update_sequence();
+                }
+
+                /// Describe this expectation if it hasn't yet been called as
+                /// many times as it requires.
+                fn unsatisfied(&self) -> Option<::std::string::String> {
+                    if self.times.is_satisfied() {
+                        None
+                    } else {
+                        Some(std::format!(
+                            "{} called {} time(s) which is fewer than expected {}{}",
+                            self.describe(),
+                            self.times.count(),
+                            self.times.minimum(),
+                            self.whence(None)))
+                    }
                 }
 
-                fn verify_sequence(&self, desc: &str) {
+                fn verify_order(&self, desc: &str,
+                    __mockall_caller: &'static ::std::panic::Location<'static>)
+                {
+                    if let Some(__mockall_handle) = &self.handle {
+                        if let Err(m) = __mockall_handle.verify(desc) {
+                            self.reporter.fail(std::format!("{}{}", m,
+                                self.whence(Some(__mockall_caller))));
+                        }
+                    }
+                }
+
+                #turn_method
+
+                fn verify_sequence(&self, desc: &str,
+                    __mockall_caller: &'static ::std::panic::Location<'static>)
+                {
                     if let Some(__mockall_handle) = &self.seq_handle {
-                        __mockall_handle.verify(desc)
+                        if let Err(m) = __mockall_handle.verify(desc) {
+                            self.reporter.fail(std::format!("{}{}", m,
+                                self.whence(Some(__mockall_caller))));
+                        }
                     }
                 }
+
+                /// Describe where this expectation was created, and where the
+                /// mock was called, if known.
+                fn whence(&self,
+                    __mockall_caller: Option<&::std::panic::Location<'_>>)
+                    -> ::std::string::String
+                {
+                    ::mockall::whence(self.location, __mockall_caller)
+                }
             }
 
             impl #ig Drop for Common #tg #wc {
                 fn drop(&mut self) {
-                    if !::std::thread::panicking() && !self.times.is_satisfied()
-                    {
-                        let desc = std::format!(
-                            "{}", self.matcher.lock().unwrap());
-                        panic!("{}: Expectation({}) called {} time(s) which is fewer than expected {}",
-                               #funcname,
-                               desc,
-                               self.times.count(),
-                               self.times.minimum());
+                    // If the sequence already reported this expectation,
+                    // don't report it twice.
+                    let __mockall_reported = matches!(&self.seq_handle,
+                        Some(__mockall_h) if __mockall_h.is_reported());
+                    if !::std::thread::panicking() && !__mockall_reported {
+                        if let Some(m) = self.unsatisfied() {
+                            self.reporter.fail(m);
+                        }
                     }
                 }
             }
@@ -1031,6 +1940,34 @@ impl<'a> ToTokens for CommonExpectationMethods<'a> {
             .map(|(argname, id)| quote!(#argname: #id, ))
             .collect::<TokenStream>();
         let v = &self.f.privmod_vis;
+        let (seq_generics, seq_ty) = self.f.seq_generics();
+        let mock_name_method = if self.f.is_named() {
+            quote!(
+                /// Record the name of the mock object that owns this
+                /// expectation.
+                #[doc(hidden)]
+                #v fn mock_name(&mut self, __mockall_name: Option<&str>)
+                    -> &mut Self
+                {
+                    self.common.mock_name =
+                        __mockall_name.map(::std::string::String::from);
+                    self
+                }
+
+                /// Record the flavor of the mock object that owns this
+                /// expectation.
+                #[doc(hidden)]
+                #v fn flavor(&mut self, __mockall_flavor: ::mockall::Flavor)
+                    -> &mut Self
+                {
+                    self.common.strict =
+                        __mockall_flavor == ::mockall::Flavor::Strict;
+                    self
+                }
+            )
+        } else {
+            quote!()
+        };
         let with_method = if self.f.concretize {
             quote!(
                 // No `with` method when concretizing generics
@@ -1050,10 +1987,54 @@ impl<'a> ToTokens for CommonExpectationMethods<'a> {
             )
         };
         quote!(
+            /// Only allow this expectation to be called after another has
+            /// been called at least as many times as it requires.  Once this
+            /// expectation has been called, the other may not be called again.
+            /// See [`ExpectationHandle`](../../../mockall/struct.ExpectationHandle.html).
+            #v fn after(&mut self,
+                __mockall_before: &::mockall::ExpectationHandle) -> &mut Self
+            {
+                self.common.after(__mockall_before);
+                self
+            }
+
+            /// Store a value computed from the arguments of every call that
+            /// this expectation handles in `captor`, for later assertions.
+            /// `f` converts the borrowed arguments into the value to store.
+            /// See [`Captor`](../../../mockall/struct.Captor.html).
+            #v fn capture<MockallT, MockallF>(&mut self,
+                __mockall_captor: &::mockall::Captor<MockallT>,
+                __mockall_f: MockallF) -> &mut Self
+                where MockallT: Send + 'static,
+                      MockallF: #hrtb Fn(#(&#predty, )*) -> MockallT
+                                + Send + 'static
+            {
+                let __mockall_captor = __mockall_captor.clone();
+                self.capturing(move |#(#argnames, )*|
+                    __mockall_captor.push(__mockall_f(#(#argnames, )*)))
+            }
+
+            /// Call `f` with the arguments of every call that this expectation
+            /// handles, before returning.  Unlike [`withf`](#method.withf),
+            /// it doesn't affect matching.
+            #v fn capturing<MockallF>(&mut self, __mockall_f: MockallF)
+                -> &mut Self
+                where MockallF: #hrtb FnMut(#(&#predty, )*) + Send + 'static
+            {
+                self.common.capturing(__mockall_f);
+                self
+            }
+
+            /// Return a handle to this expectation, so other expectations can
+            /// be ordered [`after`](#method.after) it.
+            #v fn handle(&mut self) -> ::mockall::ExpectationHandle {
+                self.common.handle()
+            }
+
             /// Add this expectation to a
             /// [`Sequence`](../../../mockall/struct.Sequence.html).
-            #v fn in_sequence(&mut self, __mockall_seq: &mut ::mockall::Sequence)
-                -> &mut Self
+            #v fn in_sequence #seq_generics (&mut self,
+                __mockall_seq: &mut #seq_ty) -> &mut Self
             {
                 self.common.in_sequence(__mockall_seq);
                 self
@@ -1063,12 +2044,31 @@ impl<'a> ToTokens for CommonExpectationMethods<'a> {
                 self.common.is_done()
             }
 
+            /// Describe how this expectation's matcher treats the arguments
+            #[allow(clippy::ptr_arg)]
+            fn explain #lg (&self, #(#argnames: &#predty, )*) -> ::std::string::String {
+                self.common.explain(#(#argnames, )*)
+            }
+
             /// Validate this expectation's matcher.
             #[allow(clippy::ptr_arg)]
             fn matches #lg (&self, #(#argnames: &#predty, )*) -> bool {
                 self.common.matches(#(#argnames, )*)
             }
 
+            #mock_name_method
+
+            /// Give this expectation a name, to identify it in failure
+            /// messages.
+            #v fn named<MockallS>(&mut self, __mockall_name: MockallS)
+                -> &mut Self
+                where MockallS: Into<::std::string::String>
+            {
+                self.common.name = Some(__mockall_name.into());
+                self.common.update_sequence();
+                self
+            }
+
             /// Forbid this expectation from ever being called.
             #v fn never(&mut self) -> &mut Self {
                 self.common.never();
@@ -1086,6 +2086,19 @@ impl<'a> ToTokens for CommonExpectationMethods<'a> {
                 self.times(1)
             }
 
+            /// Move `machine` to `state` whenever this expectation is called.
+            /// See [`StateMachine`](../../../mockall/struct.StateMachine.html).
+            #v fn then_state<MockallS>(&mut self,
+                __mockall_machine: &::mockall::StateMachine<MockallS>,
+                __mockall_state: MockallS) -> &mut Self
+                where MockallS: Clone + ::std::fmt::Debug + PartialEq + Send
+                                + Sync + 'static
+            {
+                self.common.then_state =
+                    Some(__mockall_machine.transition(__mockall_state));
+                self
+            }
+
             /// Restrict the number of times that that this method may be called.
             ///
             /// The argument may be:
@@ -1104,6 +2117,19 @@ impl<'a> ToTokens for CommonExpectationMethods<'a> {
                 self
             }
 
+            /// Only match calls while `machine` is in `state`.
+            /// See [`StateMachine`](../../../mockall/struct.StateMachine.html).
+            #v fn when_state<MockallS>(&mut self,
+                __mockall_machine: &::mockall::StateMachine<MockallS>,
+                __mockall_state: MockallS) -> &mut Self
+                where MockallS: Clone + ::std::fmt::Debug + PartialEq + Send
+                                + Sync + 'static
+            {
+                self.common.when_state =
+                    Some(__mockall_machine.guard(__mockall_state));
+                self
+            }
+
             #with_method
 
             /// Set a matching function for this Expectation.
@@ -1139,38 +2165,179 @@ struct CommonExpectationsMethods<'a> {
 
 impl<'a> ToTokens for CommonExpectationsMethods<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
+        let argnames = &self.f.argnames;
         let (ig, tg, wc) = self.f.egenerics.split_for_impl();
+        let lg = lifetimes_to_generics(&self.f.alifetimes);
+        let predty = &self.f.predty;
+        let output = self.f.rfunc_output();
         let v = &self.f.privmod_vis;
         quote!(
             /// A collection of [`Expectation`](struct.Expectations.html)
             /// objects.  Users will rarely if ever use this struct directly.
             #[doc(hidden)]
-            #v struct Expectations #ig ( Vec<Expectation #tg>) #wc;
+            #v struct Expectations #ig #wc {
+                expectations: Vec<Expectation #tg>,
+                calls: Mutex<Vec<Call>>,
+                order: ::mockall::MatchOrder
+            }
 
             impl #ig Expectations #tg #wc {
+                /// Return the call history
+                #v fn calls(&self) -> Vec<Call> {
+                    self.calls.lock().unwrap().clone()
+                }
+
                 /// Verify that all current expectations are satisfied and clear
-                /// them.
-                #v fn checkpoint(&mut self) -> std::vec::Drain<Expectation #tg>
+                /// them.  Default actions are kept.
+                #v fn checkpoint(&mut self)
+                    -> std::vec::IntoIter<Expectation #tg>
                 {
-                    self.0.drain(..)
+                    let (__mockall_d, __mockall_e): (Vec<_>, Vec<_>) =
+                        self.expectations.drain(..)
+                        .partition(|__mockall_e| __mockall_e.common.on_call);
+                    self.expectations = __mockall_d;
+                    __mockall_e.into_iter()
+                }
+
+                /// Discard the call history
+                #v fn clear_calls(&self) {
+                    self.calls.lock().unwrap().clear();
                 }
 
                 /// Create a new expectation for this method.
+                #[track_caller]
                 #v fn expect(&mut self) -> &mut Expectation #tg
                 {
-                    self.0.push(Expectation::default());
-                    let __mockall_l = self.0.len();
-                    &mut self.0[__mockall_l - 1]
+                    let mut __mockall_e = Expectation::default();
+                    __mockall_e.common.location =
+                        Some(::std::panic::Location::caller());
+                    self.expectations.push(__mockall_e);
+                    let __mockall_l = self.expectations.len();
+                    &mut self.expectations[__mockall_l - 1]
+                }
+
+                /// Find the expectation that should handle a call with these
+                /// arguments.  Default actions are only used when no ordinary
+                /// expectation matches.
+                #[allow(clippy::ptr_arg)]
+                fn find #lg (&self, #( #argnames: &#predty, )*)
+                    -> Option<usize>
+                {
+                    let __mockall_n = self.expectations.iter()
+                        .filter(|__mockall_e| !__mockall_e.common.on_call)
+                        .count();
+                    self.position(|__mockall_e|
+                              !__mockall_e.common.on_call &&
+                              __mockall_e.matches(#(#argnames, )*) &&
+                              (!__mockall_e.is_done() || __mockall_n == 1))
+                        .or_else(|| self.position(|__mockall_e|
+                                  __mockall_e.common.on_call &&
+                                  __mockall_e.matches(#(#argnames, )*)))
+                }
+
+                /// Search the expectations in the configured order.
+                fn position<F>(&self, __mockall_f: F) -> Option<usize>
+                    where F: FnMut(&Expectation #tg) -> bool
+                {
+                    match self.order {
+                        ::mockall::MatchOrder::OldestFirst =>
+                            self.expectations.iter().position(__mockall_f),
+                        ::mockall::MatchOrder::NewestFirst =>
+                            self.expectations.iter().rposition(__mockall_f)
+                    }
+                }
+
+                /// Set the order in which expectations are matched.
+                #v fn match_order(&mut self, order: ::mockall::MatchOrder) {
+                    self.order = order;
+                }
+
+                /// Explain how each expectation treats a call with these
+                /// arguments.
+                #[allow(clippy::ptr_arg)]
+                #v fn explain #lg (&self, #( #argnames: &#predty, )*) -> ::std::string::String {
+                    if self.expectations.is_empty() {
+                        return ::std::string::String::from("\nNo expectations are set");
+                    }
+                    self.expectations.iter()
+                        .map(|__mockall_e| std::format!("\n{}",
+                             __mockall_e.explain(#(#argnames, )*)))
+                        .collect()
+                }
+
+                /// Would any current expectation handle a call with these
+                /// arguments?
+                #[allow(clippy::ptr_arg)]
+                #v fn matches #lg (&self, #( #argnames: &#predty, )*) -> bool {
+                    self.find(#(#argnames, )*).is_some()
                 }
 
                 #v fn new() -> Self {
                     Self::default()
                 }
+
+                /// Return a default value, as a nice mock object does when no
+                /// expectation matches a call.
+                #v fn default_output #lg (&self)
+                    -> ::std::result::Result<#output, &'static str>
+                {
+                    use ::mockall::{ViaDefault as _, ViaReturnDefault as _};
+                    (&&::mockall::DefaultReturner::<#output>::default())
+                        .try_default()
+                }
+
+                /// Create a new default action for this method.
+                #[track_caller]
+                #v fn on_call(&mut self) -> &mut Expectation #tg
+                {
+                    let __mockall_e = self.expect();
+                    __mockall_e.common.on_call = true;
+                    __mockall_e
+                }
+
+                /// Explain why no expectation would handle a call with these
+                /// arguments, and report it to the right place.
+                #[allow(clippy::ptr_arg)]
+                #v fn no_match #lg (&self, #( #argnames: &#predty, )*)
+                    -> ::mockall::NoMatch
+                {
+                    let __mockall_reporter = ::mockall::Reporter::first_active(
+                        self.expectations.iter().map(|__mockall_e| &__mockall_e.common.reporter));
+                    ::mockall::NoMatch::new(__mockall_reporter,
+                        self.explain(#(#argnames, )*))
+                }
+
+                /// Should the next call be added to the call history?
+                #v fn recording(&self) -> bool {
+                    self.calls.lock().unwrap().len() <
+                        ::mockall::call_history_limit()
+                }
+
+                /// Add a call to the call history, unless it's full
+                #v fn record(&self, __mockall_call: Call) {
+                    let mut __mockall_calls = self.calls.lock().unwrap();
+                    if __mockall_calls.len() < ::mockall::call_history_limit() {
+                        __mockall_calls.push(__mockall_call);
+                    }
+                }
+
+                /// Describe every expectation that hasn't yet been called as
+                /// many times as it requires.
+                #v fn unsatisfied(&self) -> Vec<::std::string::String> {
+                    self.expectations.iter()
+                        .filter(|__mockall_e| !__mockall_e.common.on_call)
+                        .filter_map(|__mockall_e| __mockall_e.common.unsatisfied())
+                        .collect()
+                }
             }
             impl #ig Default for Expectations #tg #wc
             {
                 fn default() -> Self {
-                    Expectations(Vec::new())
+                    Expectations {
+                        expectations: Vec::new(),
+                        calls: Mutex::new(Vec::new()),
+                        order: ::mockall::MatchOrder::default()
+                    }
                 }
             }
         ).to_tokens(tokens);
@@ -1192,18 +2359,62 @@ impl<'a> ToTokens for ExpectationGuardCommonMethods<'a> {
         let argty = &self.f.argty;
         let (_, tg, _) = self.f.egenerics.split_for_impl();
         let keyid = gen_keyid(&self.f.egenerics);
+        // A thread-local ExpectationGuard holds the Mutex rather than its
+        // lock, so it locks it anew for each method.  Since that lock doesn't
+        // outlive the method, every ExpectationGuard returns itself rather
+        // than the Expectation.
+        let (guard, lock, self_mut) = if self.f.thread_local {
+            (quote!(__mockall_guard),
+             quote!(
+                 let __mockall_e = ::std::rc::Rc::clone(&self.guard);
+                 let mut __mockall_guard = __mockall_e.lock()
+                     .unwrap_or_else(::std::sync::PoisonError::into_inner);
+             ),
+             quote!())
+        } else {
+            (quote!(self.guard), quote!(), quote!(mut))
+        };
         let expectations = if self.f.is_expectation_generic() {
-            quote!(self.guard
+            quote!(#guard
                    .store
                    .get_mut(&::mockall::Key::new::#keyid())
                    .unwrap()
                    .downcast_mut::<Expectations #tg>()
                    .unwrap())
         } else {
-            quote!(self.guard)
+            quote!(#guard)
         };
         let hrtb = self.f.hrtb();
         let output = &self.f.output;
+        let v = &self.f.privmod_vis;
+        let (seq_generics, seq_ty) = self.f.seq_generics();
+        let returning_async = self.f.returning_async_bounds()
+            .map(|fut_bounds| quote!(
+                /// Just like
+                /// [`Expectation::returning_async`](struct.Expectation.html#method.returning_async)
+                #v fn returning_async<MockallF, MockallFut>(&mut self,
+                    __mockall_f: MockallF) -> &mut Self
+                    where MockallF: #hrtb FnMut(#(#argty, )*)
+                                    -> MockallFut + Send + 'static,
+                          MockallFut: #fut_bounds
+                {
+                    #lock
+                    #expectations.expectations[self.i].returning_async(__mockall_f);
+                    self
+                }
+            ));
+        let returning_handle = self.f.returning_async_bounds().map(|fut_bounds| quote!(
+            /// Just like
+            /// [`Expectation::returning_handle`](struct.Expectation.html#method.returning_handle)
+            #v fn returning_handle<MockallT>(&mut self)
+                -> ::mockall::FutureHandle<MockallT>
+                where MockallT: Send + 'static,
+                      ::mockall::PendingFuture<MockallT>: #fut_bounds
+            {
+                #lock
+                #expectations.expectations[self.i].returning_handle()
+            }
+        ));
         let predty = &self.f.predty;
         let with_generics_idents = (0..self.f.predty.len())
             .map(|i| format_ident!("MockallMatcher{}", i))
@@ -1217,7 +2428,6 @@ impl<'a> ToTokens for ExpectationGuardCommonMethods<'a> {
             .zip(with_generics_idents.iter())
             .map(|(argname, id)| quote!(#argname: #id, ))
             .collect::<TokenStream>();
-        let v = &self.f.privmod_vis;
         let with_method = if self.f.concretize {
             quote!()
         } else {
@@ -1225,102 +2435,216 @@ impl<'a> ToTokens for ExpectationGuardCommonMethods<'a> {
                 /// Just like
                 /// [`Expectation::with`](struct.Expectation.html#method.with)
                 #v fn with<#with_generics> (&mut self, #with_args)
-                    -> &mut Expectation #tg
+                    -> &mut Self
                 {
-                    #expectations.0[self.i].with(#(#argnames, )*)
+                    #lock
+                    #expectations.expectations[self.i].with(#(#argnames, )*);
+                    self
                 }
             )
         };
         quote!(
+            /// Just like
+            /// [`Expectation::after`](struct.Expectation.html#method.after)
+            #v fn after(&mut self,
+                __mockall_before: &::mockall::ExpectationHandle)
+                -> &mut Self
+            {
+                #lock
+                #expectations.expectations[self.i].after(__mockall_before);
+                self
+            }
+
+            /// Just like
+            /// [`Expectation::capture`](struct.Expectation.html#method.capture)
+            #v fn capture<MockallT, MockallF>(&mut self,
+                __mockall_captor: &::mockall::Captor<MockallT>,
+                __mockall_f: MockallF) -> &mut Self
+                where MockallT: Send + 'static,
+                      MockallF: #hrtb Fn(#(&#predty, )*) -> MockallT
+                                + Send + 'static
+            {
+                #lock
+                #expectations.expectations[self.i].capture(__mockall_captor, __mockall_f);
+                self
+            }
+
+            /// Just like
+            /// [`Expectation::capturing`](struct.Expectation.html#method.capturing)
+            #v fn capturing<MockallF>(&mut self, __mockall_f: MockallF)
+                -> &mut Self
+                where MockallF: #hrtb FnMut(#(&#predty, )*) + Send + 'static
+            {
+                #lock
+                #expectations.expectations[self.i].capturing(__mockall_f);
+                self
+            }
+
+            /// Just like
+            /// [`Expectation::handle`](struct.Expectation.html#method.handle)
+            #v fn handle(&mut self) -> ::mockall::ExpectationHandle {
+                #lock
+                #expectations.expectations[self.i].handle()
+            }
+
             /// Just like
             /// [`Expectation::in_sequence`](struct.Expectation.html#method.in_sequence)
-            #v fn in_sequence(&mut self,
-                __mockall_seq: &mut ::mockall::Sequence)
-                -> &mut Expectation #tg
+            #v fn in_sequence #seq_generics (&mut self,
+                __mockall_seq: &mut #seq_ty) -> &mut Self
+            {
+                #lock
+                #expectations.expectations[self.i].in_sequence(__mockall_seq);
+                self
+            }
+
+            /// Just like
+            /// [`Expectation::named`](struct.Expectation.html#method.named)
+            #v fn named<MockallS>(&mut self, __mockall_name: MockallS)
+                -> &mut Self
+                where MockallS: Into<::std::string::String>
             {
-                #expectations.0[self.i].in_sequence(__mockall_seq)
+                #lock
+                #expectations.expectations[self.i].named(__mockall_name);
+                self
             }
 
             /// Just like
             /// [`Expectation::never`](struct.Expectation.html#method.never)
-            #v fn never(&mut self) -> &mut Expectation #tg {
-                #expectations.0[self.i].never()
+            #v fn never(&mut self) -> &mut Self {
+                #lock
+                #expectations.expectations[self.i].never();
+                self
             }
 
             /// Just like
             /// [`Expectation::once`](struct.Expectation.html#method.once)
-            #v fn once(&mut self) -> &mut Expectation #tg {
-                #expectations.0[self.i].once()
+            #v fn once(&mut self) -> &mut Self {
+                #lock
+                #expectations.expectations[self.i].once();
+                self
+            }
+
+            /// Turn this expectation into a default action
+            fn on_call(#self_mut self) -> Self {
+                #lock
+                #expectations.expectations[self.i].common.on_call = true;
+                self
             }
 
             /// Just like
             /// [`Expectation::return_const`](struct.Expectation.html#method.return_const)
             #v fn return_const<MockallOutput>
             (&mut self, __mockall_c: MockallOutput)
-                -> &mut Expectation #tg
+                -> &mut Self
                 where MockallOutput: Clone + Into<#output> + Send + 'static
             {
-                #expectations.0[self.i].return_const(__mockall_c)
+                #lock
+                #expectations.expectations[self.i].return_const(__mockall_c);
+                self
             }
 
             /// Just like
             /// [`Expectation::return_const_st`](struct.Expectation.html#method.return_const_st)
             #v fn return_const_st<MockallOutput>
             (&mut self, __mockall_c: MockallOutput)
-                -> &mut Expectation #tg
+                -> &mut Self
                 where MockallOutput: Clone + Into<#output> + 'static
             {
-                #expectations.0[self.i].return_const_st(__mockall_c)
+                #lock
+                #expectations.expectations[self.i].return_const_st(__mockall_c);
+                self
             }
 
             /// Just like
             /// [`Expectation::returning`](struct.Expectation.html#method.returning)
             #v fn returning<MockallF>(&mut self, __mockall_f: MockallF)
-                -> &mut Expectation #tg
+                -> &mut Self
                 where MockallF: #hrtb FnMut(#(#argty, )*)
                     -> #output + Send + 'static
             {
-                #expectations.0[self.i].returning(__mockall_f)
+                #lock
+                #expectations.expectations[self.i].returning(__mockall_f);
+                self
             }
 
+            #returning_async
+
+            #returning_handle
+
             /// Just like
             /// [`Expectation::return_once`](struct.Expectation.html#method.return_once)
             #v fn return_once<MockallF>(&mut self, __mockall_f: MockallF)
-                -> &mut Expectation #tg
+                -> &mut Self
                 where MockallF: #hrtb FnOnce(#(#argty, )*)
                                 -> #output + Send + 'static
             {
-                #expectations.0[self.i].return_once(__mockall_f)
+                #lock
+                #expectations.expectations[self.i].return_once(__mockall_f);
+                self
             }
 
             /// Just like
             /// [`Expectation::return_once_st`](struct.Expectation.html#method.return_once_st)
             #v fn return_once_st<MockallF>(&mut self, __mockall_f: MockallF)
-                -> &mut Expectation #tg
+                -> &mut Self
                 where MockallF: #hrtb FnOnce(#(#argty, )*)
                                 -> #output + 'static
             {
-                #expectations.0[self.i].return_once_st(__mockall_f)
+                #lock
+                #expectations.expectations[self.i].return_once_st(__mockall_f);
+                self
             }
 
 
             /// Just like
             /// [`Expectation::returning_st`](struct.Expectation.html#method.returning_st)
             #v fn returning_st<MockallF>(&mut self, __mockall_f: MockallF)
-                -> &mut Expectation #tg
+                -> &mut Self
                 where MockallF: #hrtb FnMut(#(#argty, )*)
                                 -> #output + 'static
             {
-                #expectations.0[self.i].returning_st(__mockall_f)
+                #lock
+                #expectations.expectations[self.i].returning_st(__mockall_f);
+                self
+            }
+
+            /// Just like
+            /// [`Expectation::then_state`](struct.Expectation.html#method.then_state)
+            #v fn then_state<MockallS>(&mut self,
+                __mockall_machine: &::mockall::StateMachine<MockallS>,
+                __mockall_state: MockallS) -> &mut Self
+                where MockallS: Clone + ::std::fmt::Debug + PartialEq + Send
+                                + Sync + 'static
+            {
+                #lock
+                #expectations.expectations[self.i].then_state(__mockall_machine,
+                    __mockall_state);
+                self
             }
 
             /// Just like
             /// [`Expectation::times`](struct.Expectation.html#method.times)
             #v fn times<MockallR>(&mut self, __mockall_r: MockallR)
-                -> &mut Expectation #tg
+                -> &mut Self
                 where MockallR: Into<::mockall::TimesRange>
             {
-                #expectations.0[self.i].times(__mockall_r)
+                #lock
+                #expectations.expectations[self.i].times(__mockall_r);
+                self
+            }
+
+            /// Just like
+            /// [`Expectation::when_state`](struct.Expectation.html#method.when_state)
+            #v fn when_state<MockallS>(&mut self,
+                __mockall_machine: &::mockall::StateMachine<MockallS>,
+                __mockall_state: MockallS) -> &mut Self
+                where MockallS: Clone + ::std::fmt::Debug + PartialEq + Send
+                                + Sync + 'static
+            {
+                #lock
+                #expectations.expectations[self.i].when_state(__mockall_machine,
+                    __mockall_state);
+                self
             }
 
             #with_method
@@ -1328,21 +2652,25 @@ impl<'a> ToTokens for ExpectationGuardCommonMethods<'a> {
             /// Just like
             /// [`Expectation::withf`](struct.Expectation.html#method.withf)
             #v fn withf<MockallF>(&mut self, __mockall_f: MockallF)
-                -> &mut Expectation #tg
+                -> &mut Self
                 where MockallF: #hrtb Fn(#(&#predty, )*)
                                 -> bool + Send + 'static
             {
-                #expectations.0[self.i].withf(__mockall_f)
+                #lock
+                #expectations.expectations[self.i].withf(__mockall_f);
+                self
             }
 
             /// Just like
             /// [`Expectation::withf_st`](struct.Expectation.html#method.withf_st)
             #v fn withf_st<MockallF>(&mut self, __mockall_f: MockallF)
-                -> &mut Expectation #tg
+                -> &mut Self
                 where MockallF: #hrtb Fn(#(&#predty, )*)
                                 -> bool + 'static
             {
-                #expectations.0[self.i].withf_st(__mockall_f)
+                #lock
+                #expectations.expectations[self.i].withf_st(__mockall_f);
+                self
             }
         ).to_tokens(tokens);
     }
@@ -1371,13 +2699,48 @@ impl<'a> ToTokens for ConcreteExpectationGuard<'a> {
         let (e_ig, e_tg, e_wc) = e_generics.split_for_impl();
         let (ei_ig, _, _) = e_generics.split_for_impl();
         let v = &self.f.privmod_vis;
+        let expectations = self.f.expectations_static(quote!(Expectations #tg));
+        // A thread-local ExpectationGuard holds the Mutex, because its lock
+        // can't outlive the current thread's Rc.
+        let (fields, new) = if self.f.thread_local {
+            let guard_ty = quote!(
+                ::std::rc::Rc<::std::sync::Mutex<Expectations #tg>>
+            );
+            (quote!(
+                guard: #guard_ty,
+                i: usize,
+                _lifetime: ::std::marker::PhantomData<&'__mockall_lt ()>
+             ),
+             quote!(
+                #v fn new(__mockall_e: #guard_ty) -> Self {
+                    let __mockall_i = {
+                        let mut __mockall_guard = __mockall_e.lock()
+                            .unwrap_or_else(
+                                ::std::sync::PoisonError::into_inner);
+                        __mockall_guard.expect(); // Drop the &Expectation
+                        __mockall_guard.expectations.len() - 1
+                    };
+                    ExpectationGuard{guard: __mockall_e, i: __mockall_i,
+                        _lifetime: ::std::marker::PhantomData}
+                }
+             ))
+        } else {
+            (quote!(
+                guard: MutexGuard<'__mockall_lt, Expectations #tg>,
+                i: usize
+             ),
+             quote!(
+                #v fn new(mut __mockall_guard: MutexGuard<'__mockall_lt, Expectations #tg>)
+                    -> Self
+                {
+                    __mockall_guard.expect(); // Drop the &Expectation
+                    let __mockall_i = __mockall_guard.expectations.len() - 1;
+                    ExpectationGuard{guard: __mockall_guard, i: __mockall_i}
+                }
+             ))
+        };
         quote!(
-            ::mockall::lazy_static! {
-                #[doc(hidden)]
-                #v static ref EXPECTATIONS:
-                    ::std::sync::Mutex<Expectations #tg> =
-                    ::std::sync::Mutex::new(Expectations::new());
-            }
+            #expectations
             /// Like an [`&Expectation`](struct.Expectation.html) but
             /// protected by a Mutex guard.  Useful for mocking static
             /// methods.  Forwards accesses to an `Expectation` object.
@@ -1391,8 +2754,7 @@ impl<'a> ToTokens for ConcreteExpectationGuard<'a> {
             // ExpectationGuard is only defined for expectations that return
             // 'static return types.
             #v struct ExpectationGuard #e_ig #e_wc {
-                guard: MutexGuard<'__mockall_lt, Expectations #tg>,
-                i: usize
+                #fields
             }
 
             #[allow(clippy::unused_unit)]
@@ -1401,13 +2763,8 @@ impl<'a> ToTokens for ConcreteExpectationGuard<'a> {
                 // Should only be called from the mockall_derive generated
                 // code
                 #[doc(hidden)]
-                #v fn new(mut __mockall_guard: MutexGuard<'__mockall_lt, Expectations #tg>)
-                    -> Self
-                {
-                    __mockall_guard.expect(); // Drop the &Expectation
-                    let __mockall_i = __mockall_guard.0.len() - 1;
-                    ExpectationGuard{guard: __mockall_guard, i: __mockall_i}
-                }
+                #[track_caller]
+                #new
 
                 #common_methods
             }
@@ -1440,19 +2797,74 @@ impl<'a> ToTokens for GenericExpectationGuard<'a> {
         let fn_params = &self.f.fn_params;
         let tbf = tg.as_turbofish();
         let v = &self.f.privmod_vis;
+        let expectations = self.f.expectations_static(
+            quote!(GenericExpectations));
+        // Find or create the Expectations for these generic parameters, and
+        // add an Expectation to them.
+        let expect = quote!(
+            let __mockall_order = __mockall_guard.order;
+            let __mockall_ee: &mut Expectations #tg =
+                __mockall_guard.store.entry(
+                    ::mockall::Key::new::#keyid()
+                ).or_insert_with(|| {
+                    let mut __mockall_e = Expectations #tbf ::new();
+                    __mockall_e.match_order(__mockall_order);
+                    Box::new(__mockall_e)
+                }).downcast_mut()
+                .unwrap();
+            __mockall_ee.expect();    // Drop the &Expectation
+            __mockall_ee.expectations.len() - 1
+        );
+        // A thread-local ExpectationGuard holds the Mutex, because its lock
+        // can't outlive the current thread's Rc.
+        let (fields, new) = if self.f.thread_local {
+            let guard_ty = quote!(
+                ::std::rc::Rc<::std::sync::Mutex<GenericExpectations>>
+            );
+            (quote!(
+                guard: #guard_ty,
+                i: usize,
+                _phantom: ::std::marker::PhantomData<
+                    (&'__mockall_lt (), #(#fn_params,)*)
+                >,
+             ),
+             quote!(
+                #v fn new(__mockall_e: #guard_ty) -> Self {
+                    let __mockall_i = {
+                        let mut __mockall_guard = __mockall_e.lock()
+                            .unwrap_or_else(
+                                ::std::sync::PoisonError::into_inner);
+                        #expect
+                    };
+                    ExpectationGuard{guard: __mockall_e, i: __mockall_i,
+                        _phantom: ::std::marker::PhantomData}
+                }
+             ))
+        } else {
+            (quote!(
+                guard: MutexGuard<'__mockall_lt, GenericExpectations>,
+                i: usize,
+                _phantom: ::std::marker::PhantomData<(#(#fn_params,)*)>,
+             ),
+             quote!(
+                #v fn new(mut __mockall_guard: MutexGuard<'__mockall_lt, GenericExpectations>)
+                    -> Self
+                {
+                    let __mockall_i = {
+                        #expect
+                    };
+                    ExpectationGuard{guard: __mockall_guard, i: __mockall_i,
+                        _phantom: ::std::marker::PhantomData}
+                }
+             ))
+        };
         quote!(
-            ::mockall::lazy_static! {
-                #v static ref EXPECTATIONS:
-                    ::std::sync::Mutex<GenericExpectations> =
-                    ::std::sync::Mutex::new(GenericExpectations::new());
-            }
+            #expectations
             /// Like an [`&Expectation`](struct.Expectation.html) but
             /// protected by a Mutex guard.  Useful for mocking static
             /// methods.  Forwards accesses to an `Expectation` object.
             #v struct ExpectationGuard #e_ig #e_wc{
-                guard: MutexGuard<'__mockall_lt, GenericExpectations>,
-                i: usize,
-                _phantom: ::std::marker::PhantomData<(#(#fn_params,)*)>,
+                #fields
             }
 
             #[allow(clippy::unused_unit)]
@@ -1461,21 +2873,8 @@ impl<'a> ToTokens for GenericExpectationGuard<'a> {
                 // Should only be called from the mockall_derive generated
                 // code
                 #[doc(hidden)]
-                #v fn new(mut __mockall_guard: MutexGuard<'__mockall_lt, GenericExpectations>)
-                    -> Self
-                {
-                    let __mockall_ee: &mut Expectations #tg =
-                        __mockall_guard.store.entry(
-                            ::mockall::Key::new::#keyid()
-                        ).or_insert_with(||
-                            Box::new(Expectations #tbf ::new()))
-                        .downcast_mut()
-                        .unwrap();
-                    __mockall_ee.expect();    // Drop the &Expectation
-                    let __mockall_i = __mockall_ee.0.len() - 1;
-                    ExpectationGuard{guard: __mockall_guard, i: __mockall_i,
-                        _phantom: ::std::marker::PhantomData}
-                }
+                #[track_caller]
+                #new
 
                 #common_methods
             }
@@ -1514,23 +2913,47 @@ impl<'a> ToTokens for Context<'a> {
             .map(|tp| tp.ident.clone())
             .collect::<Punctuated::<Ident, Token![,]>>();
         let v = &self.f.privmod_vis;
-
-        #[cfg(not(feature = "nightly_derive"))]
-        let must_use = quote!(#[must_use =
-                "Must set return value when not using the \"nightly\" feature"
-            ]);
-        #[cfg(feature = "nightly_derive")]
-        let must_use = quote!();
+        let expectations = self.f.expectations_mutex(quote!());
+        // All static methods of one mock struct, or of one mocked module,
+        // share a lock.  Thread-local expectations don't need one.
+        let lock = if self.f.thread_local {
+            quote!(::mockall::thread_local_statics())
+        } else {
+            let levels = self.f.levels;
+            let s = self.f.struct_.as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            quote!(::mockall::lock_statics(module_path!(), #levels, #s))
+        };
+        // A thread-local ExpectationGuard can't hold the lock, so it gets the
+        // Mutex instead.
+        let guarded = if self.f.thread_local {
+            expectations.clone()
+        } else {
+            quote!(#expectations.lock()
+                .unwrap_or_else(::std::sync::PoisonError::into_inner))
+        };
+        let sync_doc = if self.f.thread_local {
+            concat!(" ",
+                "The expectations are thread-local, so tests on other threads ",
+                "can use the same method independently, but calls from other ",
+                "threads won't match them.")
+        } else {
+            concat!(" ",
+                "Until then, the `Context` holds a lock shared by all of the ",
+                "mock's static methods, so tests on other threads that use ",
+                "them will wait for it to drop.")
+        };
 
         quote!(
             /// Manages the context for expectations of static methods.
             ///
             /// Expectations on this method will be validated and cleared when
-            /// the `Context` object drops.  The `Context` object does *not*
-            /// provide any form of synchronization, so multiple tests that set
-            /// expectations on the same static method must provide their own.
+            /// the `Context` object drops, and so will its call history.
+            #[doc = #sync_doc]
             #[must_use = "Context only serves to create expectations" ]
             #v struct Context #ty_ig #ty_wc {
+                lock: ::mockall::StaticGuard,
                 // Prevent "unused type parameter" errors
                 // Surprisingly, PhantomData<Fn(generics)> is Send even if
                 // generics are not, unlike PhantomData<generics>
@@ -1539,6 +2962,16 @@ impl<'a> ToTokens for Context<'a> {
                 >
             }
             impl #ty_ig Context #ty_tg #ty_wc {
+                /// Return the arguments of every call made to this method
+                /// since the last `Context` for it dropped, once the call
+                /// history is turned on.  See
+                /// [`Call history`](../../../mockall/index.html#call-history).
+                #v fn calls(&self) -> Vec<Call> {
+                    #expectations.lock()
+                        .unwrap_or_else(::std::sync::PoisonError::into_inner)
+                        .calls()
+                }
+
                 /// Verify that all current expectations for this method are
                 /// satisfied and clear them.
                 #v fn checkpoint(&self) {
@@ -1546,31 +2979,60 @@ impl<'a> ToTokens for Context<'a> {
                 }
                 #[doc(hidden)]
                 #v fn do_checkpoint() {
-                    let __mockall_timeses = EXPECTATIONS
+                    let __mockall_timeses = #expectations
                         .lock()
-                        .unwrap()
+                        .unwrap_or_else(::std::sync::PoisonError::into_inner)
                         .checkpoint()
                         .collect::<Vec<_>>();
                 }
 
                 /// Create a new expectation for this method.
-                #must_use
+                #[track_caller]
                 #v fn expect #meth_ig ( &self,) -> ExpectationGuard #e_tg
                     #meth_wc
                 {
-                    ExpectationGuard::new(EXPECTATIONS.lock().unwrap())
+                    ExpectationGuard::new(#guarded)
+                }
+
+                /// Set the order in which this method's expectations are
+                /// matched, until the `Context` drops.
+                #v fn match_order(&self, order: ::mockall::MatchOrder) {
+                    #expectations.lock()
+                        .unwrap_or_else(::std::sync::PoisonError::into_inner)
+                        .match_order(order);
+                }
+
+                /// Create a new default action for this method, used when no
+                /// expectation matches.  It has no call count requirement of
+                /// its own.
+                #[track_caller]
+                #v fn on_call #meth_ig ( &self,) -> ExpectationGuard #e_tg
+                    #meth_wc
+                {
+                    ExpectationGuard::new(#guarded)
+                        .on_call()
                 }
             }
             impl #ty_ig Default for Context #ty_tg #ty_wc {
                 fn default() -> Self {
-                    Context {_phantom: std::marker::PhantomData}
+                    Context {
+                        lock: #lock,
+                        _phantom: std::marker::PhantomData
+                    }
                 }
             }
             impl #ty_ig Drop for Context #ty_tg #ty_wc {
                 fn drop(&mut self) {
-                    if !std::thread::panicking() {
-                        Self::do_checkpoint()
-                    }
+                    // Even if the test is panicking, discard the expectations
+                    // so they can't affect later tests.  They won't be
+                    // verified, because an Expectation doesn't report anything
+                    // when dropped during a panic.
+                    Self::do_checkpoint();
+                    let __mockall_e = &#expectations;
+                    let mut __mockall_guard = __mockall_e.lock()
+                        .unwrap_or_else(::std::sync::PoisonError::into_inner);
+                    __mockall_guard.match_order(::mockall::MatchOrder::default());
+                    __mockall_guard.clear_calls();
                 }
             }
         ).to_tokens(tokens);
@@ -1620,6 +3082,30 @@ impl<'a> ToTokens for Matcher<'a> {
         } else {
             quote!(Matcher::Pred(__mockall_pred) => [#pred_matches].iter().all(|__mockall_x| *__mockall_x),)
         };
+        let pred_explains = argnames.iter().enumerate()
+            .map(|(i, argname)| {
+                let idx = syn::Index::from(i);
+                let name = quote!(#argname).to_string();
+                quote!(
+                    if let Some(__mockall_case) =
+                        __mockall_pred.#idx.find_case(false, #argname)
+                    {
+                        __mockall_s.push_str(&::mockall::explain_rejection(
+                            #name, &__mockall_case.tree()));
+                    }
+                )
+            }).collect::<TokenStream>();
+        let predexplain_body = if self.f.concretize {
+            quote!()
+        } else {
+            quote!(
+                Matcher::Pred(__mockall_pred) => {
+                    let mut __mockall_s = ::std::string::String::new();
+                    #pred_explains
+                    __mockall_s
+                },
+            )
+        };
         let preddbg_body = if self.f.concretize {
             quote!()
         } else {
@@ -1643,6 +3129,19 @@ impl<'a> ToTokens for Matcher<'a> {
                 _Phantom(Box<dyn Fn(#(#fn_params,)*) + Send>)
             }
             impl #ig Matcher #tg #wc {
+                /// Explain which of the arguments were rejected, and why.
+                #[allow(clippy::ptr_arg)]
+                #[allow(unused_variables)]
+                fn explain #lg (&self, #( #argnames: &#predty, )*) -> ::std::string::String {
+                    match self {
+                        Matcher::Always => ::std::string::String::new(),
+                        Matcher::Func(_) | Matcher::FuncSt(_) =>
+                            ::std::string::String::from(": the function returned false"),
+                        #predexplain_body
+                        _ => unreachable!()
+                    }
+                }
+
                 #[allow(clippy::ptr_arg)]
                 fn matches #lg (&self, #( #argnames: &#predty, )*) -> bool {
                     match self {
@@ -1693,8 +3192,9 @@ impl<'a> ToTokens for RefRfunc<'a> {
         let owned_output = &self.f.owned_output;
 
         #[cfg(not(feature = "nightly_derive"))]
-        let default_err_msg =
-            "Returning default values requires the \"nightly\" feature";
+        let default_err_msg = concat!("Can only return default values for ",
+            "types that impl std::Default.  For generic types, that requires ",
+            "the \"nightly\" feature");
         #[cfg(feature = "nightly_derive")]
         let default_err_msg =
             "Can only return default values for types that impl std::Default";
@@ -1731,9 +3231,11 @@ impl<'a> ToTokens for RefRfunc<'a> {
             impl #ig std::default::Default for Rfunc #tg #wc
             {
                 fn default() -> Self {
-                    use ::mockall::ReturnDefault;
-                    Rfunc::Default(::mockall::DefaultReturner::<#owned_output>
-                                ::maybe_return_default())
+                    use ::mockall::{ViaDefault as _, ViaReturnDefault as _};
+                    Rfunc::Default(
+                        (&&::mockall::DefaultReturner::<#owned_output>
+                            ::default())
+                        .maybe_default())
                 }
             }
         ).to_tokens(tokens);
@@ -1755,8 +3257,9 @@ impl<'a> ToTokens for RefMutRfunc<'a> {
         let output = &self.f.output;
 
         #[cfg(not(feature = "nightly_derive"))]
-        let default_err_msg =
-            "Returning default values requires the \"nightly\" feature";
+        let default_err_msg = concat!("Can only return default values for ",
+            "types that impl std::Default.  For generic types, that requires ",
+            "the \"nightly\" feature");
         #[cfg(feature = "nightly_derive")]
         let default_err_msg =
             "Can only return default values for types that impl std::Default";
@@ -1821,9 +3324,11 @@ impl<'a> ToTokens for RefMutRfunc<'a> {
             impl #ig std::default::Default for Rfunc #tg #wc
             {
                 fn default() -> Self {
-                    use ::mockall::ReturnDefault;
-                    Rfunc::Default(::mockall::DefaultReturner::<#owned_output>
-                                ::maybe_return_default())
+                    use ::mockall::{ViaDefault as _, ViaReturnDefault as _};
+                    Rfunc::Default(
+                        (&&::mockall::DefaultReturner::<#owned_output>
+                            ::default())
+                        .maybe_default())
                 }
             }
         ).to_tokens(tokens);
@@ -1843,9 +3348,46 @@ impl<'a> ToTokens for StaticRfunc<'a> {
         let hrtb = self.f.hrtb();
         let lg = lifetimes_to_generics(&self.f.alifetimes);
         let output = &self.f.output;
+        let rfunc_output = self.f.rfunc_output();
+        let (async_variant, async_call, async_arm, async_ctor, ready) =
+            if self.f.is_async()
+        {
+            (
+                quote!(
+                    // Returns a future, for `returning_async`
+                    Async(Box<dyn #hrtb FnMut(#(#argty, )*)
+                        -> ::std::pin::Pin<Box<
+                            dyn ::std::future::Future<Output=#output> + Send>>
+                        + Send>),
+                ),
+                quote!(
+                    if let Rfunc::Async(__mockall_f) = self {
+                        return ::std::result::Result::Ok(
+                            ::mockall::AsyncOutput::Pending(
+                                __mockall_f(#(#argnames, )*)));
+                    }
+                ),
+                // Already handled by async_call
+                quote!(Rfunc::Async(_) => unreachable!(),),
+                quote!(
+                    fn future<MockallF>(__mockall_f: MockallF) -> Self
+                        where MockallF: #hrtb FnMut(#(#argty, )*)
+                            -> ::std::pin::Pin<Box<
+                                dyn ::std::future::Future<Output=#output> + Send>>
+                            + Send + 'static
+                    {
+                        Rfunc::Async(Box::new(__mockall_f))
+                    }
+                ),
+                quote!(.map(::mockall::AsyncOutput::Ready))
+            )
+        } else {
+            (quote!(), quote!(), quote!(), quote!(), quote!())
+        };
         quote!(
             #[allow(clippy::unused_unit)]
             enum Rfunc #ig #wc {
+                #async_variant
                 Default,
                 // Indicates that a `return_once` expectation has already
                 // returned
@@ -1868,13 +3410,17 @@ impl<'a> ToTokens for StaticRfunc<'a> {
 
             impl #ig  Rfunc #tg #wc {
                 fn call_mut #lg (&mut self, #( #argnames: #argty, )* )
-                    -> std::result::Result<#output, &'static str>
+                    -> std::result::Result<#rfunc_output, &'static str>
                 {
-                    match self {
+                    #async_call
+                    let __mockall_r = match self {
                         Rfunc::Default => {
-                            use ::mockall::ReturnDefault;
-                            ::mockall::DefaultReturner::<#output>
-                                ::return_default()
+                            use ::mockall::{
+                                ViaDefault as _,
+                                ViaReturnDefault as _
+                            };
+                            (&&::mockall::DefaultReturner::<#output>::default())
+                                .try_default()
                         },
                         Rfunc::Expired => {
                             Err("called twice, but it returns by move")
@@ -1901,9 +3447,13 @@ impl<'a> ToTokens for StaticRfunc<'a> {
                                 unreachable!()
                             }
                         },
+                        #async_arm
                         Rfunc::_Phantom(_) => unreachable!()
-                    }
+                    };
+                    __mockall_r #ready
                 }
+
+                #async_ctor
             }
 
             impl #ig std::default::Default for Rfunc #tg #wc
@@ -1926,8 +3476,8 @@ impl<'a> ToTokens for RefExpectation<'a> {
         let argnames = &self.f.argnames;
         let argty = &self.f.argty;
         let common_methods = CommonExpectationMethods{f: self.f};
-        let desc = self.f.desc();
-        let funcname = self.f.funcname();
+        let desc = self.f.desc(quote!(self.common.mock_name.as_deref()));
+        let predexprs = &self.f.predexprs;
         let (ig, tg, wc) = self.f.egenerics.split_for_impl();
 
         let (_, common_tg, _) = self.f.cgenerics.split_for_impl();
@@ -1947,14 +3497,16 @@ impl<'a> ToTokens for RefExpectation<'a> {
             #[allow(clippy::unused_unit)]
             impl #ig Expectation #tg #wc {
                 /// Call this [`Expectation`] as if it were the real method.
+                #[track_caller]
                 #v fn call #lg (&self, #(#argnames: #argty, )*) -> #output
                 {
                     self.common.call(&#desc);
+                    self.common.capture(#(#predexprs, )*);
+                    let __mockall_caller = ::std::panic::Location::caller();
                     self.rfunc.call().unwrap_or_else(|m| {
-                        let desc = std::format!(
-                            "{}", self.common.matcher.lock().unwrap());
-                        panic!("{}: Expectation({}) {}", #funcname, desc,
-                            m);
+                        self.common.reporter.fatal(std::format!("{} {}{}",
+                            self.common.describe(), m,
+                            self.common.whence(Some(__mockall_caller))))
                     })
                 }
 
@@ -1991,8 +3543,8 @@ impl<'a> ToTokens for RefMutExpectation<'a> {
         let common_methods = CommonExpectationMethods{f: self.f};
         let argnames = &self.f.argnames;
         let argty = &self.f.argty;
-        let desc = self.f.desc();
-        let funcname = self.f.funcname();
+        let desc = self.f.desc(quote!(self.common.mock_name.as_deref()));
+        let predexprs = &self.f.predexprs;
         let (ig, tg, wc) = self.f.egenerics.split_for_impl();
         let (_, common_tg, _) = self.f.cgenerics.split_for_impl();
         let lg = lifetimes_to_generics(&self.f.alifetimes);
@@ -2010,15 +3562,18 @@ impl<'a> ToTokens for RefMutExpectation<'a> {
             #[allow(clippy::unused_unit)]
             impl #ig Expectation #tg #wc {
                 /// Simulating calling the real method for this expectation
+                #[track_caller]
                 #v fn call_mut #lg (&mut self, #(#argnames: #argty, )*)
                     -> &mut #owned_output
                 {
                     self.common.call(&#desc);
-                    let desc = std::format!(
-                        "{}", self.common.matcher.lock().unwrap());
+                    self.common.capture(#(#predexprs, )*);
+                    let __mockall_caller = ::std::panic::Location::caller();
+                    let common = &self.common;
                     self.rfunc.call_mut(#(#argnames, )*).unwrap_or_else(|m| {
-                            panic!("{}: Expectation({}) {}", #funcname, desc,
-                                   m);
+                        common.reporter.fatal(std::format!("{} {}{}",
+                            common.describe(), m,
+                            common.whence(Some(__mockall_caller))))
                     })
                 }
 
@@ -2078,14 +3633,72 @@ impl<'a> ToTokens for StaticExpectation<'a> {
         let common_methods = CommonExpectationMethods{f: self.f};
         let argnames = &self.f.argnames;
         let argty = &self.f.argty;
-        let desc = self.f.desc();
+        let desc = self.f.desc(quote!(self.common.mock_name.as_deref()));
+        let predexprs = &self.f.predexprs;
         let hrtb = self.f.hrtb();
-        let funcname = self.f.funcname();
         let (ig, tg, wc) = self.f.egenerics.split_for_impl();
         let (_, common_tg, _) = self.f.cgenerics.split_for_impl();
         let lg = lifetimes_to_generics(&self.f.alifetimes);
         let output = &self.f.output;
+        let rfunc_output = self.f.rfunc_output();
         let v = &self.f.privmod_vis;
+        let returning_async = match self.f.returning_async_bounds() {
+            Some(fut_bounds) if self.f.is_async() => quote!(
+                /// Supply a closure that will provide the return value for
+                /// this `Expectation` asynchronously.  The mock method will
+                /// `.await` the future that the closure returns.  The method's
+                /// arguments are passed to the closure by value.
+                #v fn returning_async<MockallF, MockallFut>(&mut self,
+                    mut __mockall_f: MockallF) -> &mut Self
+                    where MockallF: #hrtb FnMut(#(#argty, )*)
+                                    -> MockallFut + Send + 'static,
+                          MockallFut: #fut_bounds
+                {
+                    {
+                        let mut __mockall_guard = self.rfunc.lock().unwrap();
+                        *__mockall_guard.deref_mut() = Rfunc::future(
+                            move |#(#argnames, )*|
+                                Box::pin(__mockall_f(#(#argnames, )*)));
+                    }
+                    self
+                }
+            ),
+            Some(fut_bounds) => quote!(
+                /// Supply a closure that will provide the return value for
+                /// this `Expectation` asynchronously.  The closure's future
+                /// will be boxed and pinned for it.  The method's arguments are
+                /// passed to the closure by value.
+                #v fn returning_async<MockallF, MockallFut>(&mut self,
+                    mut __mockall_f: MockallF) -> &mut Self
+                    where MockallF: #hrtb FnMut(#(#argty, )*)
+                                    -> MockallFut + Send + 'static,
+                          MockallFut: #fut_bounds
+                {
+                    self.returning(move |#(#argnames, )*|
+                        Box::pin(__mockall_f(#(#argnames, )*)))
+                }
+            ),
+            None => quote!()
+        };
+        let returning_handle = self.f.returning_async_bounds().map(|fut_bounds| quote!(
+            /// Return a future that stays pending until the test completes it
+            /// through the returned [`FutureHandle`](::mockall::FutureHandle).
+            /// Every call to the method waits on the same handle.
+            // MockallT is always the method's output type.  It's generic only
+            // so the bounds won't be checked for methods that can't use it.
+            #[allow(unused_variables)]
+            #v fn returning_handle<MockallT>(&mut self)
+                -> ::mockall::FutureHandle<MockallT>
+                where MockallT: Send + 'static,
+                      ::mockall::PendingFuture<MockallT>: #fut_bounds
+            {
+                let __mockall_h = ::mockall::FutureHandle::new();
+                let __mockall_h2 = __mockall_h.clone();
+                self.returning_async(move |#(#argnames, )*|
+                    __mockall_h2.future());
+                __mockall_h
+            }
+        ));
 
         quote!(
             /// Expectation type for methods that return a `'static` type.
@@ -2099,15 +3712,18 @@ impl<'a> ToTokens for StaticExpectation<'a> {
             impl #ig Expectation #tg #wc {
                 /// Call this [`Expectation`] as if it were the real method.
                 #[doc(hidden)]
-                #v fn call #lg (&self, #(#argnames: #argty, )* ) -> #output
+                #[track_caller]
+                #v fn call #lg (&self, #(#argnames: #argty, )* )
+                    -> #rfunc_output
                 {
                     self.common.call(&#desc);
+                    self.common.capture(#(#predexprs, )*);
+                    let __mockall_caller = ::std::panic::Location::caller();
                     self.rfunc.lock().unwrap().call_mut(#(#argnames, )*)
                         .unwrap_or_else(|message| {
-                            let desc = std::format!(
-                                "{}", self.common.matcher.lock().unwrap());
-                            panic!("{}: Expectation({}) {}", #funcname, desc,
-                                   message);
+                            self.common.reporter.fatal(std::format!("{} {}{}",
+                                self.common.describe(), message,
+                                self.common.whence(Some(__mockall_caller))))
                         })
                 }
 
@@ -2207,6 +3823,10 @@ impl<'a> ToTokens for StaticExpectation<'a> {
                     self
                 }
 
+                #returning_async
+
+                #returning_handle
+
                 /// Single-threaded version of [`returning`](#method.returning).
                 /// Can be used when the argument or return type isn't `Send`.
                 ///
@@ -2260,18 +3880,20 @@ impl<'a> ToTokens for RefExpectations<'a> {
             #common_methods
             impl #ig Expectations #tg #wc {
                 /// Simulate calling the real method.  Every current expectation
-                /// will be checked in FIFO order and the first one with
-                /// matching arguments will be used.
+                /// will be checked in the configured order (FIFO by default)
+                /// and the first one with matching arguments will be used.  If
+                /// none match, explain why not.
+                #[track_caller]
                 #v fn call #lg (&self, #(#argnames: #argty, )* )
-                    -> Option<#output>
+                    -> ::std::result::Result<#output, ::mockall::NoMatch>
                 {
-                    self.0.iter()
-                        .find(|__mockall_e|
-                              __mockall_e.matches(#(#predexprs, )*) &&
-                              (!__mockall_e.is_done() || self.0.len() == 1))
-                        .map(move |__mockall_e|
-                             __mockall_e.call(#(#argnames),*)
-                        )
+                    match self.find(#(#predexprs, )*) {
+                        Some(__mockall_i) =>
+                            ::std::result::Result::Ok(
+                                self.expectations[__mockall_i].call(#(#argnames),*)),
+                        None => ::std::result::Result::Err(
+                            self.no_match(#(#predexprs, )*))
+                    }
                 }
 
             }
@@ -2298,19 +3920,20 @@ impl<'a> ToTokens for RefMutExpectations<'a> {
             #common_methods
             impl #ig Expectations #tg #wc {
                 /// Simulate calling the real method.  Every current expectation
-                /// will be checked in FIFO order and the first one with
-                /// matching arguments will be used.
+                /// will be checked in the configured order (FIFO by default)
+                /// and the first one with matching arguments will be used.  If
+                /// none match, explain why not.
+                #[track_caller]
                 #v fn call_mut #lg (&mut self, #(#argnames: #argty, )* )
-                    -> Option<#output>
+                    -> ::std::result::Result<#output, ::mockall::NoMatch>
                 {
-                    let __mockall_n = self.0.len();
-                    self.0.iter_mut()
-                        .find(|__mockall_e|
-                              __mockall_e.matches(#(#predexprs, )*) &&
-                              (!__mockall_e.is_done() || __mockall_n == 1))
-                        .map(move |__mockall_e|
-                             __mockall_e.call_mut(#(#argnames, )*)
-                        )
+                    match self.find(#(#predexprs, )*) {
+                        Some(__mockall_i) =>
+                            ::std::result::Result::Ok(
+                                self.expectations[__mockall_i].call_mut(#(#argnames, )*)),
+                        None => ::std::result::Result::Err(
+                            self.no_match(#(#predexprs, )*))
+                    }
                 }
 
             }
@@ -2330,27 +3953,47 @@ impl<'a> ToTokens for StaticExpectations<'a> {
         let argty = &self.f.argty;
         let (ig, tg, wc) = self.f.egenerics.split_for_impl();
         let lg = lifetimes_to_generics(&self.f.alifetimes);
-        let output = &self.f.output;
+        let output = self.f.rfunc_output();
         let predexprs = &self.f.predexprs;
         let v = &self.f.privmod_vis;
+        let predty = &self.f.predty;
+        let turn_method = if self.f.awaits_turn() {
+            quote!(
+                /// Return a future that resolves once the expectation that
+                /// would handle a call with these arguments may be called.
+                #[allow(clippy::ptr_arg)]
+                #v fn turn #lg (&self, #( #argnames: &#predty, )*)
+                    -> ::mockall::SeqTurn
+                {
+                    self.find(#(#argnames, )*)
+                        .map(|__mockall_i| self.expectations[__mockall_i].common.turn())
+                        .unwrap_or_default()
+                }
+            )
+        } else {
+            quote!()
+        };
         quote!(
             #common_methods
             impl #ig Expectations #tg #wc {
                 /// Simulate calling the real method.  Every current expectation
-                /// will be checked in FIFO order and the first one with
-                /// matching arguments will be used.
+                /// will be checked in the configured order (FIFO by default)
+                /// and the first one with matching arguments will be used.  If
+                /// none match, explain why not.
+                #[track_caller]
                 #v fn call #lg (&self, #(#argnames: #argty, )* )
-                    -> Option<#output>
+                    -> ::std::result::Result<#output, ::mockall::NoMatch>
                 {
-                    self.0.iter()
-                        .find(|__mockall_e|
-                              __mockall_e.matches(#(#predexprs, )*) &&
-                              (!__mockall_e.is_done() || self.0.len() == 1))
-                        .map(move |__mockall_e|
-                             __mockall_e.call(#(#argnames, )*)
-                        )
+                    match self.find(#(#predexprs, )*) {
+                        Some(__mockall_i) =>
+                            ::std::result::Result::Ok(
+                                self.expectations[__mockall_i].call(#(#argnames, )*)),
+                        None => ::std::result::Result::Err(
+                            self.no_match(#(#predexprs, )*))
+                    }
                 }
 
+                #turn_method
             }
         ).to_tokens(tokens);
     }
@@ -2378,22 +4021,69 @@ impl<'a> ToTokens for GenericExpectations<'a> {
             #[doc(hidden)]
             #[derive(Default)]
             #v struct GenericExpectations{
+                calls: Mutex<Vec<Call>>,
+                order: ::mockall::MatchOrder,
                 store: std::collections::hash_map::HashMap<::mockall::Key,
                                Box<dyn ::mockall::AnyExpectations>>
             }
             impl GenericExpectations {
+                /// Return the call history.  This applies to all sets of
+                /// generic parameters!
+                #v fn calls(&self) -> Vec<Call> {
+                    self.calls.lock().unwrap().clone()
+                }
+
                 /// Verify that all current expectations are satisfied and clear
                 /// them.  This applies to all sets of generic parameters!
-                #v fn checkpoint(&mut self) ->
-                    std::collections::hash_map::Drain<::mockall::Key,
-                               Box<dyn ::mockall::AnyExpectations>>
+                #v fn checkpoint(&mut self)
+                    -> std::vec::IntoIter<Box<dyn Send>>
                 {
-                    self.store.drain()
+                    self.store.values_mut()
+                        .flat_map(|__mockall_e| __mockall_e.checkpoint())
+                        .collect::<Vec<_>>()
+                        .into_iter()
+                }
+
+                /// Discard the call history
+                #v fn clear_calls(&self) {
+                    self.calls.lock().unwrap().clear();
+                }
+
+                /// Set the order in which expectations are matched.  This
+                /// applies to all sets of generic parameters!
+                #v fn match_order(&mut self, order: ::mockall::MatchOrder) {
+                    self.order = order;
+                    for __mockall_e in self.store.values_mut() {
+                        __mockall_e.match_order(order);
+                    }
                 }
 
                 #v fn new() -> Self {
                     Self::default()
                 }
+
+                /// Should the next call be added to the call history?
+                #v fn recording(&self) -> bool {
+                    self.calls.lock().unwrap().len() <
+                        ::mockall::call_history_limit()
+                }
+
+                /// Add a call to the call history, unless it's full
+                #v fn record(&self, __mockall_call: Call) {
+                    let mut __mockall_calls = self.calls.lock().unwrap();
+                    if __mockall_calls.len() < ::mockall::call_history_limit() {
+                        __mockall_calls.push(__mockall_call);
+                    }
+                }
+
+                /// Describe every expectation that hasn't yet been called as
+                /// many times as it requires.  This applies to all sets of
+                /// generic parameters!
+                #v fn unsatisfied(&self) -> Vec<::std::string::String> {
+                    self.store.values()
+                        .flat_map(|__mockall_e| __mockall_e.unsatisfied())
+                        .collect()
+                }
             }
             #ge
         ).to_tokens(tokens);
@@ -2419,8 +4109,28 @@ impl<'a> ToTokens for StaticGenericExpectations<'a> {
             send_syncify(&mut any_wc, self.f.owned_output.clone());
         }
         let tbf = tg.as_turbofish();
-        let output = &self.f.output;
+        let output = self.f.rfunc_output();
         let v = &self.f.privmod_vis;
+        let predty = &self.f.predty;
+        let turn_method = if self.f.awaits_turn() {
+            quote!(
+                /// Return a future that resolves once the expectation that
+                /// would handle a call with these arguments may be called.
+                #[allow(clippy::ptr_arg)]
+                #v fn turn #ig (&self, #( #argnames: &#predty, )*)
+                    -> ::mockall::SeqTurn #wc
+                {
+                    self.store.get(&::mockall::Key::new::#keyid())
+                        .map(|__mockall_e|
+                            __mockall_e.downcast_ref::<Expectations #tg>()
+                            .unwrap()
+                            .turn(#(#argnames, )*))
+                        .unwrap_or_default()
+                }
+            )
+        } else {
+            quote!()
+        };
         let (call, get, self_, downcast) = if self.f.return_refmut {
             (format_ident!("call_mut"),
              format_ident!("get_mut"),
@@ -2433,29 +4143,82 @@ impl<'a> ToTokens for StaticGenericExpectations<'a> {
              format_ident!("downcast_ref"))
         };
         quote!(
-            impl #ig ::mockall::AnyExpectations for Expectations #tg #any_wc {}
+            impl #ig ::mockall::AnyExpectations for Expectations #tg #any_wc {
+                fn checkpoint(&mut self) -> Vec<Box<dyn Send>> {
+                    Expectations::checkpoint(self)
+                        .map(|__mockall_e| Box::new(__mockall_e) as Box<dyn Send>)
+                        .collect()
+                }
+
+                fn match_order(&mut self, order: ::mockall::MatchOrder) {
+                    Expectations::match_order(self, order)
+                }
+
+                fn unsatisfied(&self) -> Vec<::std::string::String> {
+                    Expectations::unsatisfied(self)
+                }
+            }
             impl GenericExpectations {
                 /// Simulating calling the real method.
+                #[track_caller]
                 #v fn #call #ig (#self_, #(#argnames: #argty, )* )
-                    -> Option<#output> #wc
+                    -> ::std::result::Result<#output, ::mockall::NoMatch> #wc
                 {
-                    self.store.#get(&::mockall::Key::new::#keyid())
-                        .map(|__mockall_e| {
+                    match self.store.#get(&::mockall::Key::new::#keyid()) {
+                        Some(__mockall_e) =>
                             __mockall_e.#downcast::<Expectations #tg>()
                             .unwrap()
-                            .#call(#(#argnames, )*)
-                        }).flatten()
+                            .#call(#(#argnames, )*),
+                        None => ::std::result::Result::Err(::mockall::NoMatch::new(
+                            ::mockall::Reporter::current(),
+                            ::std::string::String::from(
+                                "\nNo expectations are set for these generic parameters")))
+                    }
                 }
 
                 /// Create a new Expectation.
+                #[track_caller]
                 #v fn expect #ig (&mut self) -> &mut Expectation #tg #any_wc
                 {
+                    let __mockall_order = self.order;
                     self.store.entry(::mockall::Key::new::#keyid())
-                        .or_insert_with(|| Box::new(Expectations #tbf::new()))
+                        .or_insert_with(|| {
+                            let mut __mockall_e = Expectations #tbf::new();
+                            __mockall_e.match_order(__mockall_order);
+                            Box::new(__mockall_e)
+                        })
                         .downcast_mut::<Expectations #tg>()
                         .unwrap()
                         .expect()
                 }
+
+                /// Create a new default action.
+                #[track_caller]
+                #v fn on_call #ig (&mut self) -> &mut Expectation #tg #any_wc
+                {
+                    let __mockall_order = self.order;
+                    self.store.entry(::mockall::Key::new::#keyid())
+                        .or_insert_with(|| {
+                            let mut __mockall_e = Expectations #tbf::new();
+                            __mockall_e.match_order(__mockall_order);
+                            Box::new(__mockall_e)
+                        })
+                        .downcast_mut::<Expectations #tg>()
+                        .unwrap()
+                        .on_call()
+                }
+
+                /// Return a default value, as a nice mock object does when no
+                /// expectation matches a call.
+                #v fn default_output #ig (&self)
+                    -> ::std::result::Result<#output, &'static str> #wc
+                {
+                    use ::mockall::{ViaDefault as _, ViaReturnDefault as _};
+                    (&&::mockall::DefaultReturner::<#output>::default())
+                        .try_default()
+                }
+
+                #turn_method
             }
         ).to_tokens(tokens)
     }
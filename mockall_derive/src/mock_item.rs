@@ -44,7 +44,26 @@ pub(crate) struct MockItemModule {
     vis: Visibility,
     mock_ident: Ident,
     orig_ident: Option<Ident>,
-    content: Vec<MockItemContent>
+    content: Vec<MockItemContent>,
+    /// Does the original module already have a `context` or `StaticContext`
+    /// item?
+    has_static_context: bool
+}
+
+/// Is `ident` one of the names used by a mocked module's `StaticContext`?
+fn is_static_context_ident(ident: &Ident) -> bool {
+    ident == "context" || ident == "StaticContext"
+}
+
+/// Does this `use` tree import something as `context` or `StaticContext`?
+fn uses_static_context(tree: &UseTree) -> bool {
+    match tree {
+        UseTree::Path(p) => uses_static_context(&p.tree),
+        UseTree::Name(n) => is_static_context_ident(&n.ident),
+        UseTree::Rename(r) => is_static_context_ident(&r.rename),
+        UseTree::Group(g) => g.items.iter().any(uses_static_context),
+        UseTree::Glob(_) => false
+    }
 }
 
 impl From<MockableModule> for MockItemModule {
@@ -52,8 +71,22 @@ impl From<MockableModule> for MockItemModule {
         let mock_ident = mod_.mock_ident.clone();
         let orig_ident = mod_.orig_ident;
         let mut content = Vec::new();
+        let mut has_static_context = false;
         for item in mod_.content.into_iter() {
             let span = item.span();
+            has_static_context |= match &item {
+                Item::Const(ic) => is_static_context_ident(&ic.ident),
+                Item::Fn(f) => is_static_context_ident(&f.sig.ident),
+                Item::ForeignMod(ifm) => ifm.items.iter().any(|fi|
+                    matches!(fi, ForeignItem::Fn(f)
+                        if is_static_context_ident(&f.sig.ident))
+                ),
+                Item::Static(is) => is_static_context_ident(&is.ident),
+                Item::TraitAlias(ta) => is_static_context_ident(&ta.ident),
+                Item::Type(ty) => is_static_context_ident(&ty.ident),
+                Item::Use(u) => uses_static_context(&u.tree),
+                _ => false
+            };
             match item {
                 Item::ExternCrate(_) | Item::Impl(_) =>
                 {
@@ -75,6 +108,7 @@ impl From<MockableModule> for MockItemModule {
                         .parent(&mock_ident)
                         .levels(1)
                         .call_levels(0)
+                        .thread_local(mod_.thread_local)
                         .build();
                     content.push(MockItemContent::Fn(Box::new(mf)));
                 },
@@ -90,6 +124,7 @@ impl From<MockableModule> for MockItemModule {
                                 .parent(&mock_ident)
                                 .levels(1)
                                 .call_levels(0)
+                                .thread_local(mod_.thread_local)
                                 .build();
                             content.push(MockItemContent::Fn(Box::new(mf)));
                         } else {
@@ -128,7 +163,8 @@ impl From<MockableModule> for MockItemModule {
             vis: mod_.vis,
             mock_ident: mod_.mock_ident,
             orig_ident,
-            content
+            content,
+            has_static_context
         }
     }
 }
@@ -137,6 +173,10 @@ impl ToTokens for MockItemModule {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let mut body = TokenStream::new();
         let mut cp_body = TokenStream::new();
+        let mut ctx_fields = Vec::new();
+        let mut ctx_inits = Vec::new();
+        let mut ctx_checkpoints = Vec::new();
+        let mut ctx_methods = Vec::new();
         let attrs = &self.attrs;
         let modname = &self.mock_ident;
         let vis = &self.vis;
@@ -154,6 +194,10 @@ impl ToTokens for MockItemModule {
                         #ctx_fn
                     ).to_tokens(&mut body);
                     f.checkpoint().to_tokens(&mut cp_body);
+                    ctx_fields.push(f.static_context_field(None));
+                    ctx_inits.push(f.static_context_init());
+                    ctx_checkpoints.push(f.static_context_checkpoint());
+                    ctx_methods.push(f.static_context_methods(None));
                 },
             }
         }
@@ -163,6 +207,34 @@ impl ToTokens for MockItemModule {
             /// this module are satisfied and clear them.
             pub fn checkpoint() { #cp_body }
         ).to_tokens(&mut body);
+        if !self.has_static_context && !ctx_fields.is_empty() {
+            quote!(
+                /// Manages the context for expectations of every function in
+                /// this module.
+                ///
+                /// Each function's expectations will be validated and cleared
+                /// when the `StaticContext` object drops.
+                #[must_use = "StaticContext only serves to create expectations"]
+                pub struct StaticContext {
+                    #(#ctx_fields),*
+                }
+                impl StaticContext {
+                    /// Verify that all current expectations for every function
+                    /// in this module are satisfied and clear them.
+                    pub fn checkpoint(&self) {
+                        #(#ctx_checkpoints)*
+                    }
+                    #(#ctx_methods)*
+                }
+                /// Create a [`StaticContext`] for mocking every function in
+                /// this module at once
+                pub fn context() -> StaticContext {
+                    StaticContext {
+                        #(#ctx_inits),*
+                    }
+                }
+            ).to_tokens(&mut body);
+        }
         let docstr = {
             if let Some(ident) = &self.orig_ident {
                 let inner = format!("Mock version of the `{}` module", ident);
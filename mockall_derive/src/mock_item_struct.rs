@@ -91,6 +91,21 @@ impl Methods {
             .collect::<Vec<_>>()
     }
 
+    fn match_orders(&self) -> Vec<impl ToTokens> {
+        self.0.iter()
+            .filter(|meth| !meth.is_static())
+            .map(|meth| meth.match_order())
+            .collect::<Vec<_>>()
+    }
+
+    /// Return a fragment of code to collect every unsatisfied expectation
+    fn unsatisfieds(&self) -> Vec<impl ToTokens> {
+        self.0.iter()
+            .filter(|meth| !meth.is_static())
+            .map(|meth| meth.unsatisfied())
+            .collect::<Vec<_>>()
+    }
+
     /// Return a fragment of code to initialize struct fields during default()
     fn default_inits(&self) -> Vec<TokenStream> {
         self.0.iter()
@@ -124,13 +139,31 @@ pub(crate) struct MockItemStruct {
     generics: Generics,
     /// Should Mockall generate a Debug implementation?
     auto_debug: bool,
+    /// Does the original struct have a `match_order` method?
+    has_match_order: bool,
+    /// Does the original struct have a `named` method?
+    has_named: bool,
     /// Does the original struct have a `new` method?
     has_new: bool,
+    /// Does the original struct have a `new_nice` method?
+    has_new_nice: bool,
+    /// Does the original struct have a `new_strict` method?
+    has_new_strict: bool,
+    /// Does the original struct have a `satisfied` method?
+    has_satisfied: bool,
+    /// Does the original struct have a `static_context` method?
+    has_static_context: bool,
+    /// Does the original struct have a `wait_satisfied` method?
+    has_wait_satisfied: bool,
+    /// Does the original struct have a `wrapping` method?
+    has_wrapping: bool,
     /// Inherent methods of the mock struct
     methods: Methods,
     /// Name of the overall module that holds all of the mock stuff
     modname: Ident,
     name: Ident,
+    /// Inherent methods that keep their original bodies
+    real_methods: Vec<ImplItemMethod>,
     /// Is this a whole MockStruct or just a substructure for a trait impl?
     traits: Vec<MockTrait>,
     vis: Visibility,
@@ -156,6 +189,83 @@ impl MockItemStruct {
         }
     }
 
+    /// Does the mock struct have any non-static methods, whose failure
+    /// messages could include the mock object's name?
+    fn is_nameable(&self) -> bool {
+        !self.methods.all_static() ||
+            self.traits.iter()
+                .any(|trait_| trait_.methods.iter().any(|m| !m.is_static()))
+    }
+
+    fn named_method(&self) -> impl ToTokens {
+        if self.has_named || !self.is_nameable() {
+            return TokenStream::new();
+        }
+        quote!(
+            /// Create a new mock object with no expectations, that will be
+            /// identified by `name` in failure messages.
+            ///
+            /// This method will not be generated if the real struct already
+            /// has a `named` method.
+            pub fn named<__MockallS>(name: __MockallS) -> Self
+                where __MockallS: Into<::std::string::String>
+            {
+                let mut __mockall_mock = Self::default();
+                __mockall_mock.__mockall_name = Some(name.into());
+                __mockall_mock
+            }
+        ).into_token_stream()
+    }
+
+    /// Constructors for mock objects that treat calls not covered by their
+    /// expectations differently from `new`.
+    fn flavor_methods(&self) -> impl ToTokens {
+        if !self.is_nameable() {
+            return TokenStream::new();
+        }
+        let new_nice = if self.has_new_nice {
+            TokenStream::new()
+        } else {
+            quote!(
+                /// Create a new nice mock object with no expectations.
+                ///
+                /// When no expectation or default action matches a call, a
+                /// nice mock returns the default value of the method's return
+                /// type instead of failing.  Static methods aren't affected.
+                ///
+                /// This method will not be generated if the real struct
+                /// already has a `new_nice` method.
+                pub fn new_nice() -> Self {
+                    let mut __mockall_mock = Self::default();
+                    __mockall_mock.__mockall_flavor = ::mockall::Flavor::Nice;
+                    __mockall_mock
+                }
+            )
+        };
+        let new_strict = if self.has_new_strict {
+            TokenStream::new()
+        } else {
+            quote!(
+                /// Create a new strict mock object with no expectations.
+                ///
+                /// A strict mock fails any call that isn't handled by an
+                /// expectation with a call count.  That includes calls handled
+                /// by expectations that may be called any number of times,
+                /// like the ones with no `times` at all, and calls handled by
+                /// default actions.  Static methods aren't affected.
+                ///
+                /// This method will not be generated if the real struct
+                /// already has a `new_strict` method.
+                pub fn new_strict() -> Self {
+                    let mut __mockall_mock = Self::default();
+                    __mockall_mock.__mockall_flavor = ::mockall::Flavor::Strict;
+                    __mockall_mock
+                }
+            )
+        };
+        quote!(#new_nice #new_strict)
+    }
+
     fn new_method(&self) -> impl ToTokens {
         if self.has_new {
             TokenStream::new()
@@ -175,6 +285,54 @@ impl MockItemStruct {
         }
     }
 
+    /// Traits whose methods can forward calls to a wrapped real object
+    fn passthrough_traits(&self) -> Vec<&MockTrait> {
+        unique_trait_iter(self.traits.iter())
+            .filter(|trait_| trait_.is_passthrough())
+            .collect()
+    }
+
+    /// Name of the trait that forwards calls to a wrapped real object
+    fn passthrough_ident(&self) -> Ident {
+        format_ident!("__mockall_{}_Real", self.name)
+    }
+
+    /// Generate the trait that forwards calls to a wrapped real object, and
+    /// its blanket implementation for all types that implement the mocked
+    /// traits.
+    fn passthrough_trait(&self) -> impl ToTokens {
+        let traits = self.passthrough_traits();
+        if traits.is_empty() {
+            return TokenStream::new();
+        }
+        let (ig, tg, wc) = self.generics.split_for_impl();
+        let ident = self.passthrough_ident();
+        let bounds = traits.iter()
+            .map(|trait_| trait_.passthrough_bound())
+            .collect::<Vec<_>>();
+        let (decls, impls): (Vec<_>, Vec<_>) = traits.iter()
+            .map(|trait_| trait_.passthrough_methods())
+            .unzip();
+        let decls = decls.into_iter().flatten();
+        let impls = impls.into_iter().flatten();
+        let mut rgenerics = self.generics.clone();
+        rgenerics.params.push(parse2(quote!(__MockallReal)).unwrap());
+        rgenerics.make_where_clause().predicates.push(
+            parse2(quote!(__MockallReal: #(#bounds)+*)).unwrap());
+        let (rig, _, rwc) = rgenerics.split_for_impl();
+        quote!(
+            #[allow(non_camel_case_types)]
+            #[allow(non_snake_case)]
+            trait #ident #ig #wc {
+                #(#decls)*
+            }
+            #[allow(non_snake_case)]
+            impl #rig #ident #tg for __MockallReal #rwc {
+                #(#impls)*
+            }
+        ).into_token_stream()
+    }
+
     fn phantom_default_inits(&self) -> Vec<TokenStream> {
         phantom_default_inits(&self.generics)
     }
@@ -182,6 +340,173 @@ impl MockItemStruct {
     fn phantom_fields(&self) -> Vec<TokenStream> {
         phantom_fields(&self.generics)
     }
+
+    /// Generate a `StaticContext` structure that manages the Contexts of all
+    /// static methods, and the `static_context` method that creates it.
+    fn static_context(&self) -> (TokenStream, TokenStream) {
+        // Every static method, with its private module and any attributes of
+        // its trait impl
+        let mut statics = self.methods.0.iter()
+            .filter(|meth| meth.is_static())
+            .map(|meth| (meth, self.modname.clone(), Vec::new()))
+            .collect::<Vec<_>>();
+        for trait_ in self.traits.iter() {
+            let modname = format_ident!("{}_{}", &self.modname,
+                                        trait_.ss_name());
+            let attrs = AttrFormatter::new(&trait_.attrs)
+                .async_trait(false)
+                .doc(false)
+                .format();
+            statics.extend(trait_.methods.iter()
+                .filter(|meth| meth.is_static())
+                .map(|meth| (meth, modname.clone(), attrs.clone())));
+        }
+        if self.has_static_context || statics.is_empty() {
+            return (TokenStream::new(), TokenStream::new());
+        }
+        let mut fields = statics.iter()
+            .map(|(meth, modname, attrs)| {
+                let field = meth.static_context_field(Some(modname));
+                quote!(#(#attrs)* #field)
+            }).collect::<Vec<_>>();
+        fields.extend(self.phantom_fields());
+        let mut inits = statics.iter()
+            .map(|(meth, _, attrs)| {
+                let init = meth.static_context_init();
+                quote!(#(#attrs)* #init)
+            }).collect::<Vec<_>>();
+        inits.extend(self.phantom_default_inits());
+        let checkpoints = statics.iter()
+            .map(|(meth, _, attrs)| {
+                let checkpoint = meth.static_context_checkpoint();
+                quote!(#(#attrs)* #checkpoint)
+            });
+        let methods = statics.iter()
+            .map(|(meth, modname, attrs)| {
+                let methods = meth.static_context_methods(Some(modname));
+                quote!(#(#attrs)* #methods)
+            });
+        let (ig, tg, wc) = self.generics.split_for_impl();
+        let ident = format_ident!("{}StaticContext", &self.name);
+        let docstr = format!(
+            "Manages the context for expectations of all of [`{}`]'s static \
+            methods.", self.name);
+        let vis = &self.vis;
+        let item = quote!(
+            #[doc = #docstr]
+            ///
+            /// Each method's expectations will be validated and cleared when
+            /// the `StaticContext` object drops.
+            #[must_use = "StaticContext only serves to create expectations" ]
+            #vis struct #ident #ig #wc {
+                #(#fields),*
+            }
+            impl #ig #ident #tg #wc {
+                /// Verify that all current expectations for every static
+                /// method are satisfied and clear them.
+                pub fn checkpoint(&self) {
+                    #(#checkpoints)*
+                }
+                #(#methods)*
+            }
+        );
+        let docstr = format!(
+            "Create a [`{}`] for mocking all of the static methods at once",
+            ident);
+        let method = quote!(
+            #[doc = #docstr]
+            pub fn static_context() -> #ident #tg {
+                #ident {
+                    #(#inits),*
+                }
+            }
+        );
+        (item, method)
+    }
+
+    /// Methods that wait until every expectation is satisfied.
+    /// `substruct_unsatisfieds` collects the unsatisfied expectations of each
+    /// trait's methods.
+    fn satisfied_methods(&self, substruct_unsatisfieds: &[TokenStream])
+        -> impl ToTokens
+    {
+        if self.has_satisfied && self.has_wait_satisfied {
+            return TokenStream::new();
+        }
+        let method_unsatisfieds = self.methods.unsatisfieds();
+        let satisfied = if self.has_satisfied {
+            TokenStream::new()
+        } else {
+            quote!(
+                /// Return a future that resolves once every expectation for
+                /// every method has been called at least as many times as it
+                /// requires.  Expectations of static methods are not
+                /// considered.
+                ///
+                /// This method will not be generated if the real struct
+                /// already has a `satisfied` method.
+                pub fn satisfied(&self) -> ::mockall::Satisfied<'_> {
+                    ::mockall::Satisfied::new(
+                        move || self.__mockall_unsatisfied())
+                }
+            )
+        };
+        let wait_satisfied = if self.has_wait_satisfied {
+            TokenStream::new()
+        } else {
+            quote!(
+                /// Block until every expectation for every method has been
+                /// called at least as many times as it requires.  If `timeout`
+                /// expires first, return every expectation that still isn't
+                /// satisfied.  Expectations of static methods are not
+                /// considered.
+                ///
+                /// This method will not be generated if the real struct
+                /// already has a `wait_satisfied` method.
+                pub fn wait_satisfied(&self, timeout: ::std::time::Duration)
+                    -> ::std::result::Result<(), ::mockall::MockErrors>
+                {
+                    ::mockall::wait_satisfied(timeout,
+                        || self.__mockall_unsatisfied())
+                }
+            )
+        };
+        quote!(
+            #satisfied
+            #wait_satisfied
+            fn __mockall_unsatisfied(&self)
+                -> ::std::vec::Vec<::std::string::String>
+            {
+                #[allow(unused_mut)]
+                let mut __mockall_unsatisfied = ::std::vec::Vec::new();
+                #(#substruct_unsatisfieds)*
+                #(#method_unsatisfieds)*
+                __mockall_unsatisfied
+            }
+        ).into_token_stream()
+    }
+
+    fn wrapping_method(&self) -> impl ToTokens {
+        let bounds = self.passthrough_traits().iter()
+            .map(|trait_| trait_.passthrough_bound())
+            .collect::<Vec<_>>();
+        if self.has_wrapping || bounds.is_empty() {
+            return TokenStream::new();
+        }
+        quote!(
+            /// Create a new mock object with no expectations, that forwards
+            /// every call that matches no expectation to `real`.
+            ///
+            /// See [`Passthrough`](../mockall/index.html#passthrough).
+            pub fn wrapping<__MockallReal>(real: __MockallReal) -> Self
+                where __MockallReal: #(#bounds +)* Send + Sync + 'static
+            {
+                let mut __mockall_mock = Self::default();
+                __mockall_mock.__mockall_real = Some(::std::boxed::Box::new(real));
+                __mockall_mock
+            }
+        ).into_token_stream()
+    }
 }
 
 impl From<MockableStruct> for MockItemStruct {
@@ -191,18 +516,32 @@ impl From<MockableStruct> for MockItemStruct {
         let generics = mockable.generics.clone();
         let struct_name = &mockable.name;
         let vis = mockable.vis;
-        let has_new = mockable.methods.iter()
-            .any(|meth| meth.sig.ident == "new") ||
-            mockable.impls.iter()
+        // Does the original struct have a method with this name?
+        let (methods, real_methods, impls) =
+            (&mockable.methods, &mockable.real_methods, &mockable.impls);
+        let has_method = |name: &str| methods.iter()
+            .chain(real_methods.iter())
+            .any(|meth| meth.sig.ident == name) ||
+            impls.iter()
             .any(|impl_|
                 impl_.items.iter()
                     .any(|ii| if let ImplItem::Method(iim) = ii {
-                            iim.sig.ident == "new"
+                            iim.sig.ident == name
                         } else {
                             false
                         }
                     )
             );
+        let has_match_order = has_method("match_order");
+        let has_named = has_method("named");
+        let has_new = has_method("new");
+        let has_new_nice = has_method("new_nice");
+        let has_new_strict = has_method("new_strict");
+        let has_satisfied = has_method("satisfied");
+        let has_static_context = has_method("static_context");
+        let has_wait_satisfied = has_method("wait_satisfied");
+        let has_wrapping = has_method("wrapping");
+        let thread_local = mockable.thread_local;
         let methods = Methods(mockable.methods.into_iter()
             .map(|meth|
                 mock_function::Builder::new(&meth.sig, &meth.vis)
@@ -211,11 +550,13 @@ impl From<MockableStruct> for MockItemStruct {
                     .struct_generics(&generics)
                     .levels(2)
                     .call_levels(0)
+                    .thread_local(thread_local)
                     .build()
             ).collect::<Vec<_>>());
         let structname = &mockable.name;
         let traits = mockable.impls.into_iter()
-            .map(|i| MockTrait::new(structname, &generics, i, &vis))
+            .map(|i| MockTrait::new(structname, &generics, i, &vis,
+                                    thread_local))
             .collect();
 
         MockItemStruct {
@@ -223,10 +564,19 @@ impl From<MockableStruct> for MockItemStruct {
             auto_debug,
             consts: mockable.consts,
             generics,
+            has_match_order,
+            has_named,
             has_new,
+            has_new_nice,
+            has_new_strict,
+            has_satisfied,
+            has_static_context,
+            has_wait_satisfied,
+            has_wrapping,
             methods,
             modname,
             name: mockable.name,
+            real_methods: mockable.real_methods,
             traits,
             vis
         }
@@ -239,6 +589,7 @@ impl ToTokens for MockItemStruct {
             .async_trait(false)
             .format();
         let consts = &self.consts;
+        let real_methods = &self.real_methods;
         let debug_impl = self.debug_impl();
         let struct_name = &self.name;
         let (ig, tg, wc) = self.generics.split_for_impl();
@@ -246,6 +597,10 @@ impl ToTokens for MockItemStruct {
         let calls = self.methods.0.iter()
             .map(|meth| meth.call(Some(modname)))
             .collect::<Vec<_>>();
+        let calls_fns = self.methods.0.iter()
+            .filter(|meth| !meth.is_static())
+            .map(|meth| meth.calls_fn(modname))
+            .collect::<Vec<_>>();
         let contexts = self.methods.0.iter()
             .filter(|meth| meth.is_static())
             .map(|meth| meth.context_fn(Some(modname)))
@@ -255,7 +610,11 @@ impl ToTokens for MockItemStruct {
             .map(|meth| meth.expect(modname, None))
             .collect::<Vec<_>>();
         let method_checkpoints = self.methods.checkpoints();
+        let named_method = self.named_method();
         let new_method = self.new_method();
+        let flavor_methods = self.flavor_methods();
+        let wrapping_method = self.wrapping_method();
+        let (static_context, static_context_method) = self.static_context();
         let priv_mods = self.methods.priv_mods();
         let substructs = unique_trait_iter(self.traits.iter())
             .map(|trait_| {
@@ -280,6 +639,52 @@ impl ToTokens for MockItemStruct {
                 let fieldname = &ss.fieldname;
                 quote!(#(#attrs)* self.#fieldname.checkpoint();)
             }).collect::<Vec<_>>();
+        let substruct_match_orders = substructs.iter()
+            .filter(|ss| !ss.all_static())
+            .map(|ss| {
+                let attrs = AttrFormatter::new(&ss.attrs)
+                    .async_trait(false)
+                    .doc(false)
+                    .format();
+                let fieldname = &ss.fieldname;
+                quote!(#(#attrs)* self.#fieldname.match_order(__mockall_order);)
+            }).collect::<Vec<_>>();
+        let match_order_method = if self.is_nameable() && !self.has_match_order
+        {
+            let method_match_orders = self.methods.match_orders();
+            quote!(
+                /// Set the order in which every method's expectations are
+                /// matched.  Static methods aren't affected.  See
+                /// [`MatchOrder`](::mockall::MatchOrder).
+                ///
+                /// This method will not be generated if the real struct
+                /// already has a `match_order` method.
+                pub fn match_order(&mut self,
+                    __mockall_order: ::mockall::MatchOrder)
+                {
+                    #(#substruct_match_orders)*
+                    #(#method_match_orders)*
+                }
+            )
+        } else {
+            TokenStream::new()
+        };
+        let substruct_unsatisfieds = substructs.iter()
+            .filter(|ss| !ss.all_static())
+            .map(|ss| {
+                let attrs = AttrFormatter::new(&ss.attrs)
+                    .async_trait(false)
+                    .doc(false)
+                    .format();
+                let fieldname = &ss.fieldname;
+                quote!(
+                    #(#attrs)*
+                    __mockall_unsatisfied.extend(
+                        self.#fieldname.__mockall_unsatisfied());
+                )
+            }).collect::<Vec<_>>();
+        let satisfied_methods = self.satisfied_methods(
+            &substruct_unsatisfieds);
         let mut field_definitions = substructs.iter()
             .filter(|ss| !ss.all_static())
             .map(|ss| {
@@ -293,6 +698,21 @@ impl ToTokens for MockItemStruct {
             }).collect::<Vec<_>>();
         field_definitions.extend(self.methods.field_definitions(modname));
         field_definitions.extend(self.phantom_fields());
+        let passthrough_trait = self.passthrough_trait();
+        if !self.passthrough_traits().is_empty() {
+            let ident = self.passthrough_ident();
+            field_definitions.push(quote!(
+                __mockall_real: Option<::std::boxed::Box<dyn #ident #tg + Send + Sync>>
+            ));
+        }
+        if self.is_nameable() {
+            field_definitions.push(quote!(
+                __mockall_name: Option<::std::string::String>
+            ));
+            field_definitions.push(quote!(
+                __mockall_flavor: ::mockall::Flavor
+            ));
+        }
         let mut default_inits = substructs.iter()
             .filter(|ss| !ss.all_static())
             .map(|ss| {
@@ -305,6 +725,15 @@ impl ToTokens for MockItemStruct {
             }).collect::<Vec<_>>();
         default_inits.extend(self.methods.default_inits());
         default_inits.extend(self.phantom_default_inits());
+        if !self.passthrough_traits().is_empty() {
+            default_inits.push(quote!(__mockall_real: None));
+        }
+        if self.is_nameable() {
+            default_inits.push(quote!(__mockall_name: None));
+            default_inits.push(quote!(
+                __mockall_flavor: ::mockall::Flavor::Default
+            ));
+        }
         let trait_impls = self.traits.iter()
             .map(|trait_| {
                 let modname = format_ident!("{}_{}", &self.modname,
@@ -339,17 +768,27 @@ impl ToTokens for MockItemStruct {
             #(#substructs)*
             impl #ig #struct_name #tg #wc {
                 #(#consts)*
+                #(#real_methods)*
                 #(#calls)*
                 #(#contexts)*
                 #(#expects)*
+                #(#calls_fns)*
                 /// Validate that all current expectations for all methods have
                 /// been satisfied, and discard them.
                 pub fn checkpoint(&mut self) {
                     #(#substruct_expectations)*
                     #(#method_checkpoints)*
                 }
+                #satisfied_methods
+                #match_order_method
+                #named_method
                 #new_method
+                #flavor_methods
+                #wrapping_method
+                #static_context_method
             }
+            #static_context
+            #passthrough_trait
             #(#trait_impls)*
         ).to_tokens(tokens);
     }
@@ -392,6 +831,8 @@ impl ToTokens for MockItemTraitImpl {
         let (ig, tg, wc) = self.generics.split_for_impl();
         let modname = &self.modname;
         let method_checkpoints = self.methods.checkpoints();
+        let method_match_orders = self.methods.match_orders();
+        let method_unsatisfieds = self.methods.unsatisfieds();
         let mut default_inits = self.methods.default_inits();
         default_inits.extend(self.phantom_default_inits());
         let mut field_definitions = self.methods.field_definitions(modname);
@@ -428,6 +869,21 @@ impl ToTokens for MockItemTraitImpl {
                 pub fn checkpoint(&mut self) {
                     #(#method_checkpoints)*
                 }
+                /// Set the order in which every method's expectations are
+                /// matched.
+                pub fn match_order(&mut self,
+                    __mockall_order: ::mockall::MatchOrder)
+                {
+                    #(#method_match_orders)*
+                }
+                fn __mockall_unsatisfied(&self)
+                    -> ::std::vec::Vec<::std::string::String>
+                {
+                    #[allow(unused_mut)]
+                    let mut __mockall_unsatisfied = ::std::vec::Vec::new();
+                    #(#method_unsatisfieds)*
+                    __mockall_unsatisfied
+                }
             }
         ).to_tokens(tokens);
     }
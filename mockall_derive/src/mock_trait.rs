@@ -1,5 +1,5 @@
 // vim: tw=80
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream};
 use quote::{ToTokens, format_ident, quote};
 use std::{
    collections::hash_map::DefaultHasher,
@@ -21,6 +21,8 @@ pub(crate) struct MockTrait {
     pub consts: Vec<ImplItemConst>,
     pub generics: Generics,
     pub methods: Vec<MockFunction>,
+    /// Can calls to this trait's methods be forwarded to a wrapped real object?
+    passthrough: bool,
     /// Internally-used name of the trait used.
     pub ss_name: Ident,
     /// Fully-qualified name of the trait
@@ -62,7 +64,8 @@ impl MockTrait {
     pub fn new(structname: &Ident,
                struct_generics: &Generics,
                impl_: ItemImpl,
-               vis: &Visibility) -> Self
+               vis: &Visibility,
+               thread_local: bool) -> Self
     {
         let mut consts = Vec::new();
         let mut methods = Vec::new();
@@ -74,6 +77,21 @@ impl MockTrait {
             Path::from(format_ident!("__mockall_invalid"))
         };
         let ss_name = MockTrait::ss_name_priv(&trait_path);
+        // Calls can only be forwarded to a real object if the forwarding trait
+        // can use the struct's own generics, and without attributes like
+        // #[cfg()] that would have to apply to its trait bounds.
+        let (sig, _, swc) = struct_generics.split_for_impl();
+        let (iig, _, iwc) = impl_.generics.split_for_impl();
+        let passthrough = AttrFormatter::new(&impl_.attrs)
+            .async_trait(false)
+            .doc(false)
+            .format()
+            .is_empty()
+            && quote!(#sig #swc).to_string() == quote!(#iig #iwc).to_string()
+            && impl_.items.iter().all(|ii| match ii {
+                ImplItem::Type(iit) => iit.generics.params.is_empty(),
+                _ => true
+            });
         let self_path = match *impl_.self_ty {
             Type::Path(mut type_path) =>
                 type_path.path.segments.pop().unwrap().into_value(),
@@ -97,6 +115,8 @@ impl MockTrait {
                         .struct_(structname)
                         .struct_generics(struct_generics)
                         .trait_(&ss_name)
+                        .passthrough(passthrough)
+                        .thread_local(thread_local)
                         .build();
                     methods.push(mf);
                 },
@@ -109,11 +129,13 @@ impl MockTrait {
                 }
             }
         }
+        let passthrough = methods.iter().any(MockFunction::is_passthrough);
         MockTrait {
             attrs: impl_.attrs,
             consts,
             generics: impl_.generics,
             methods,
+            passthrough,
             ss_name,
             trait_path,
             self_path,
@@ -122,6 +144,44 @@ impl MockTrait {
         }
     }
 
+    /// Can calls to any of this trait's methods be forwarded to a wrapped real
+    /// object?
+    pub fn is_passthrough(&self) -> bool {
+        self.passthrough
+    }
+
+    /// The trait bound that a wrapped real object must satisfy, including the
+    /// mock's associated types.
+    pub fn passthrough_bound(&self) -> Path {
+        let mut path = self.trait_path.clone();
+        if !self.types.is_empty() {
+            let seg = path.segments.last_mut().unwrap();
+            if let PathArguments::None = seg.arguments {
+                seg.arguments = PathArguments::AngleBracketed(
+                    parse2(quote!(<>)).unwrap());
+            }
+            if let PathArguments::AngleBracketed(abga) = &mut seg.arguments {
+                for iit in self.types.iter() {
+                    abga.args.push(GenericArgument::Binding(Binding {
+                        ident: iit.ident.clone(),
+                        eq_token: iit.eq_token,
+                        ty: iit.ty.clone()
+                    }));
+                }
+            }
+        }
+        path
+    }
+
+    /// Generate each forwardable method's declaration within the trait that
+    /// forwards calls to a wrapped real object, and its implementation.
+    pub fn passthrough_methods(&self) -> (Vec<TokenStream>, Vec<TokenStream>) {
+        self.methods.iter()
+            .filter(|meth| meth.is_passthrough())
+            .map(|meth| meth.passthrough_method(&self.trait_path))
+            .unzip()
+    }
+
     /// Generate code for the trait implementation on the mock struct
     ///
     /// # Arguments
@@ -141,6 +201,10 @@ impl MockTrait {
         let calls = self.methods.iter()
                 .map(|meth| meth.call(Some(modname)))
                 .collect::<Vec<_>>();
+        let calls_fns = self.methods.iter()
+            .filter(|meth| !meth.is_static())
+            .map(|meth| meth.calls_fn(modname))
+            .collect::<Vec<_>>();
         let contexts = self.methods.iter()
             .filter(|meth| meth.is_static())
             .map(|meth| meth.context_fn(Some(modname)))
@@ -169,6 +233,7 @@ impl MockTrait {
             #(#impl_attrs)*
             impl #ig #self_path #wc {
                 #(#expects)*
+                #(#calls_fns)*
                 #(#contexts)*
             }
         )
@@ -29,14 +29,20 @@ pub(crate) enum MockableItem {
 impl From<(Attrs, Item)> for MockableItem {
     fn from((attrs, item): (Attrs, Item)) -> MockableItem {
         match item {
-            Item::Impl(item_impl) =>
-                MockableItem::Struct(MockableStruct::from(item_impl)),
+            Item::Impl(item_impl) => {
+                let mut mockable = MockableStruct::from(item_impl);
+                mockable.thread_local = attrs.thread_local;
+                MockableItem::Struct(mockable)
+            },
             Item::ForeignMod(item_foreign_mod) =>
                 MockableItem::Module(
                     MockableModule::from((attrs, item_foreign_mod))
                 ),
-            Item::Mod(item_mod) =>
-                MockableItem::Module(MockableModule::from(item_mod)),
+            Item::Mod(item_mod) => {
+                let mut mockable = MockableModule::from(item_mod);
+                mockable.thread_local = attrs.thread_local;
+                MockableItem::Module(mockable)
+            },
             Item::Trait(trait_) =>
                 MockableItem::Struct(MockableStruct::from((attrs, trait_))),
             _ => panic!("automock does not support this item type")
@@ -56,12 +62,15 @@ pub(crate) struct MockableModule {
     pub mock_ident: Ident,
     /// Ident of the original module, if any
     pub orig_ident: Option<Ident>,
-    pub content: Vec<Item>
+    pub content: Vec<Item>,
+    /// Keep the expectations of functions in thread-local storage
+    pub thread_local: bool
 }
 
 impl From<(Attrs, ItemForeignMod)> for MockableModule {
     fn from((attrs, foreign): (Attrs, ItemForeignMod)) -> MockableModule {
         let orig_ident = None;
+        let thread_local = attrs.thread_local;
         let mock_ident = attrs.modname.expect(concat!(
             "module name is required when mocking foreign functions,",
             " like `#[automock(mod mock_ffi)]`"
@@ -142,7 +151,8 @@ impl From<(Attrs, ItemForeignMod)> for MockableModule {
             vis,
             mock_ident,
             orig_ident,
-            content
+            content,
+            thread_local
         }
     }
 }
@@ -167,7 +177,8 @@ impl From<ItemMod> for MockableModule {
             vis,
             mock_ident,
             orig_ident,
-            content
+            content,
+            thread_local: false
         }
     }
 }
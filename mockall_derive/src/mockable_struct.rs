@@ -122,6 +122,15 @@ fn mock_ident_in_type(ty: &mut Type) {
     };
 }
 
+/// Is this the `#[mockall::real]` attribute?
+fn is_real_attr(attr: &Attribute) -> bool {
+    let segments = attr.path.segments.iter()
+        .map(|ps| ps.ident.to_string())
+        .collect::<Vec<_>>();
+    segments == ["mockall", "real"] ||
+        (attr.path.leading_colon.is_none() && segments == ["real"])
+}
+
 /// Performs transformations on the ItemImpl to make it mockable
 fn mockable_item_impl(mut impl_: ItemImpl, name: &Ident, generics: &Generics)
     -> ItemImpl
@@ -138,6 +147,7 @@ fn mockable_item_impl(mut impl_: ItemImpl, name: &Ident, generics: &Generics)
 /// Performs transformations on the method to make it mockable
 fn mockable_method(meth: &mut ImplItemMethod, name: &Ident, generics: &Generics)
 {
+    sanity_check_attrs(&meth.attrs);
     demutify(&mut meth.sig.inputs);
     deselfify_args(&mut meth.sig.inputs, name, generics);
     add_lifetime_parameters(&mut meth.sig);
@@ -156,6 +166,7 @@ fn mockable_trait_method(
     name: &Ident,
     generics: &Generics)
 {
+    sanity_check_attrs(&meth.attrs);
     demutify(&mut meth.sig.inputs);
     deselfify_args(&mut meth.sig.inputs, name, generics);
     add_lifetime_parameters(&mut meth.sig);
@@ -220,6 +231,13 @@ fn mockable_trait(trait_: ItemTrait, name: &Ident, generics: &Generics)
     }
 }
 
+fn sanity_check_attrs(attrs: &[Attribute]) {
+    for attr in attrs.iter().filter(|attr| is_real_attr(attr)) {
+        compile_error(attr.span(),
+            "#[mockall::real] is only supported for methods of inherent impl blocks with #[automock]");
+    }
+}
+
 fn sanity_check_sig(sig: &Signature) {
     for arg in sig.inputs.iter() {
         if let FnArg::Typed(pt) = arg {
@@ -302,6 +320,11 @@ pub(crate) struct MockableStruct {
     /// Inherent methods of the mockable struct
     pub methods: Vec<ImplItemMethod>,
     pub name: Ident,
+    /// Inherent methods that keep their original bodies instead of being
+    /// mocked
+    pub real_methods: Vec<ImplItemMethod>,
+    /// Keep the expectations of static methods in thread-local storage
+    pub thread_local: bool,
     pub vis: Visibility,
     pub impls: Vec<ItemImpl>
 }
@@ -336,6 +359,7 @@ impl MockableStruct {
 impl From<(Attrs, ItemTrait)> for MockableStruct {
     fn from((attrs, item_trait): (Attrs, ItemTrait)) -> MockableStruct {
         let trait_ = attrs.substitute_trait(&item_trait);
+        let thread_local = attrs.thread_local;
         let mut attrs = trait_.attrs.clone();
         attrs.push(derive_debug());
         let vis = trait_.vis.clone();
@@ -349,6 +373,8 @@ impl From<(Attrs, ItemTrait)> for MockableStruct {
             name,
             generics,
             methods: Vec::new(),
+            real_methods: Vec::new(),
+            thread_local,
             impls
         }
     }
@@ -372,6 +398,7 @@ impl From<ItemImpl> for MockableStruct {
         let mut consts = Vec::new();
         let generics = item_impl.generics.clone();
         let mut methods = Vec::new();
+        let mut real_methods = Vec::new();
         let pub_token = Token![pub](Span::call_site());
         let vis = Visibility::Public(VisPublic{pub_token});
         let mut impls = Vec::new();
@@ -402,8 +429,15 @@ impl From<ItemImpl> for MockableStruct {
             for item in item_impl.items.into_iter() {
                 match item {
                     ImplItem::Method(mut meth) => {
-                        mockable_method(&mut meth, &name, &item_impl.generics);
-                        methods.push(meth)
+                        let nattrs = meth.attrs.len();
+                        meth.attrs.retain(|attr| !is_real_attr(attr));
+                        if meth.attrs.len() < nattrs {
+                            real_methods.push(meth);
+                        } else {
+                            mockable_method(&mut meth, &name,
+                                            &item_impl.generics);
+                            methods.push(meth)
+                        }
                     },
                     ImplItem::Const(iic) => consts.push(iic),
                     // Rust doesn't allow types in an inherent impl
@@ -418,6 +452,8 @@ impl From<ItemImpl> for MockableStruct {
             generics,
             methods,
             name,
+            real_methods,
+            thread_local: false,
             vis,
             impls,
         }
@@ -480,6 +516,8 @@ impl Parse for MockableStruct {
                 generics,
                 methods,
                 name,
+                real_methods: Vec::new(),
+                thread_local: false,
                 vis,
                 impls
             }
@@ -609,6 +647,30 @@ mod add_lifetime_parameters {
 
 }
 
+mod is_real_attr {
+    use super::*;
+
+    fn attr(ts: TokenStream) -> Attribute {
+        let meth: ImplItemMethod = parse2(quote!(#ts fn foo(&self);)).unwrap();
+        meth.attrs[0].clone()
+    }
+
+    #[test]
+    fn mockall_real() {
+        assert!(is_real_attr(&attr(quote!(#[mockall::real]))));
+    }
+
+    #[test]
+    fn other_crate() {
+        assert!(!is_real_attr(&attr(quote!(#[foo::real]))));
+    }
+
+    #[test]
+    fn real() {
+        assert!(is_real_attr(&attr(quote!(#[real]))));
+    }
+}
+
 mod sanity_check_sig {
     use super::*;
 